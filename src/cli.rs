@@ -0,0 +1,128 @@
+//! Standalone `render` subcommand generating pages from a stored `FileDescriptorSet`.
+
+use anyhow::{anyhow, Context, Result};
+use prost::Message;
+use prost_types::compiler::code_generator_response::File;
+use prost_types::FileDescriptorSet;
+use protoc_gen_mdbook::{generate, request_from_descriptor_set, Options};
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "usage: protoc-gen-mdbook render --descriptor-set <FILE> [--out <DIR>] \
+                     [--file <PROTO>]... [--opt <OPTIONS>]";
+
+/// Command line arguments of the `render` subcommand.
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    descriptor_set: PathBuf,
+    out: PathBuf,
+    files: Vec<String>,
+    parameter: Option<String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut descriptor_set = None;
+        let mut result = Self {
+            out: PathBuf::from("."),
+            ..Default::default()
+        };
+
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing value for `{arg}`\n{USAGE}"))
+            };
+
+            match arg.as_str() {
+                "--descriptor-set" => descriptor_set = Some(PathBuf::from(value()?)),
+                "--out" => result.out = PathBuf::from(value()?),
+                "--file" => result.files.push(value()?),
+                "--opt" => result.parameter = Some(value()?),
+                _ => return Err(anyhow!("unknown argument `{arg}`\n{USAGE}")),
+            }
+        }
+
+        result.descriptor_set =
+            descriptor_set.ok_or_else(|| anyhow!("missing `--descriptor-set`\n{USAGE}"))?;
+
+        Ok(result)
+    }
+}
+
+/// Run `render` subcommand with the command line `args` following the subcommand.
+pub fn run(args: &[String]) -> Result<()> {
+    let args = Args::parse(args)?;
+
+    let path = &args.descriptor_set;
+    let buf = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    let set = FileDescriptorSet::decode(&*buf)?;
+    let request = request_from_descriptor_set(set, args.files);
+    let options = args.parameter.as_deref().map(Options::from_parameter);
+
+    write_files(&args.out, generate(&request, &options.unwrap_or_default())?)
+}
+
+/// Write generated `files` relative to `out`, creating directories as needed.
+pub fn write_files(out: &Path, files: Vec<File>) -> Result<()> {
+    for file in files {
+        let path = out.join(file.name());
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("creating {}", parent.display()))?;
+        }
+
+        std::fs::write(&path, file.content())
+            .with_context(|| format!("writing {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Args;
+    use std::path::PathBuf;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn parse_render_args() {
+        let parsed = Args::parse(&args(&[
+            "--descriptor-set",
+            "api.pb",
+            "--out",
+            "docs",
+            "--file",
+            "foo.proto",
+            "--file",
+            "bar.proto",
+            "--opt",
+            "types:all",
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            parsed,
+            Args {
+                descriptor_set: PathBuf::from("api.pb"),
+                out: PathBuf::from("docs"),
+                files: vec!["foo.proto".to_string(), "bar.proto".to_string()],
+                parameter: Some("types:all".to_string()),
+            }
+        );
+
+        let parsed = Args::parse(&args(&["--descriptor-set", "api.pb"])).unwrap();
+        assert_eq!(parsed.out, PathBuf::from("."));
+        assert!(parsed.files.is_empty());
+
+        assert!(Args::parse(&args(&[])).is_err());
+        assert!(Args::parse(&args(&["--descriptor-set"])).is_err());
+        assert!(Args::parse(&args(&["--descriptor-set", "api.pb", "--foo"])).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! `compile` subcommand parsing proto files with the embedded compiler instead of `protoc`.
+
+use crate::cli;
+use anyhow::{anyhow, Result};
+use prost_types::compiler::CodeGeneratorRequest;
+use protoc_gen_mdbook::{generate, request_from_descriptor_set, Options};
+use std::path::PathBuf;
+
+const USAGE: &str = "usage: protoc-gen-mdbook compile [-I <DIR>]... [--out <DIR>] \
+                     [--opt <OPTIONS>] <PROTO>...";
+
+/// Command line arguments of the `compile` subcommand.
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    includes: Vec<PathBuf>,
+    out: PathBuf,
+    files: Vec<PathBuf>,
+    parameter: Option<String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut result = Self {
+            out: PathBuf::from("."),
+            ..Default::default()
+        };
+
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| anyhow!("missing value for `{arg}`\n{USAGE}"))
+            };
+
+            match arg.as_str() {
+                "-I" | "--proto_path" => result.includes.push(PathBuf::from(value()?)),
+                "--out" => result.out = PathBuf::from(value()?),
+                "--opt" => result.parameter = Some(value()?),
+                _ if arg.starts_with("-I") => result.includes.push(PathBuf::from(&arg[2..])),
+                _ if arg.starts_with('-') => {
+                    return Err(anyhow!("unknown argument `{arg}`\n{USAGE}"))
+                }
+                _ => result.files.push(PathBuf::from(arg)),
+            }
+        }
+
+        if result.files.is_empty() {
+            return Err(anyhow!("no proto files given\n{USAGE}"));
+        }
+
+        if result.includes.is_empty() {
+            result.includes.push(PathBuf::from("."));
+        }
+
+        Ok(result)
+    }
+}
+
+/// Compile `files` found in `includes` to a request including source info and all imports.
+fn compile_request(files: &[PathBuf], includes: &[PathBuf]) -> Result<CodeGeneratorRequest> {
+    let mut compiler = protox::Compiler::new(includes)?;
+    compiler
+        .include_source_info(true)
+        .include_imports(true)
+        .open_files(files)?;
+
+    let names = compiler
+        .files()
+        .filter(|file| !file.is_import())
+        .map(|file| file.name().to_string())
+        .collect();
+
+    Ok(request_from_descriptor_set(
+        compiler.file_descriptor_set(),
+        names,
+    ))
+}
+
+/// Run `compile` subcommand with the command line `args` following the subcommand.
+pub fn run(args: &[String]) -> Result<()> {
+    let args = Args::parse(args)?;
+    let request = compile_request(&args.files, &args.includes)?;
+    let options = args.parameter.as_deref().map(Options::from_parameter);
+
+    cli::write_files(&args.out, generate(&request, &options.unwrap_or_default())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compile_request, Args};
+    use protoc_gen_mdbook::{generate, Options};
+    use std::path::PathBuf;
+
+    #[test]
+    fn parse_compile_args() {
+        let args = [
+            "-I",
+            "proto",
+            "-Ivendor",
+            "proto/a.proto",
+            "--out",
+            "book/src",
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+        let parsed = Args::parse(&args).unwrap();
+        assert_eq!(
+            parsed.includes,
+            vec![PathBuf::from("proto"), PathBuf::from("vendor")]
+        );
+        assert_eq!(parsed.files, vec![PathBuf::from("proto/a.proto")]);
+        assert_eq!(parsed.out, PathBuf::from("book/src"));
+        assert!(parsed.parameter.is_none());
+
+        assert!(Args::parse(&["-I".to_string(), "proto".to_string()]).is_err());
+    }
+
+    #[test]
+    fn compile_with_comments() {
+        let dir = std::env::temp_dir().join(format!(
+            "protoc-gen-mdbook-compile-test-{}",
+            std::process::id()
+        ));
+
+        // The directory is removed before any failure of writing or compiling surfaces.
+        let request = (|| {
+            std::fs::create_dir_all(dir.join("foo"))?;
+            std::fs::write(
+                dir.join("foo/bar.proto"),
+                "syntax = \"proto3\";\npackage foo;\n// The message.\nmessage Bar {}\n",
+            )?;
+            compile_request(&[dir.join("foo/bar.proto")], std::slice::from_ref(&dir))
+        })();
+        let _ = std::fs::remove_dir_all(&dir);
+        let request = request.unwrap();
+
+        assert_eq!(request.file_to_generate, vec!["foo/bar.proto"]);
+
+        let options = Options::default().all_types(true);
+        let files = generate(&request, &options).unwrap();
+        assert_eq!(files[0].name(), "foo.bar.proto.md");
+        assert!(files[0].content().contains(" The message.\n"));
+    }
+}
@@ -0,0 +1,151 @@
+//! Expansion of `{{#proto ...}}` directives in hand-written Markdown, e.g. chapters of an mdBook:
+//!
+//! * `{{#proto path/to/foo.proto}}` renders all services of a proto file,
+//! * `{{#proto path/to/foo.proto Foo}}` renders only service `Foo` and
+//! * `{{#proto-message pkg.Bar}}` renders message or enum type `pkg.Bar`.
+
+use crate::{proto, render, Options};
+use anyhow::{anyhow, Result};
+use askama::Template;
+use prost_types::compiler::CodeGeneratorRequest;
+
+/// Expands directives using descriptors of all files in a request.
+pub struct Expander<'a> {
+    request: &'a CodeGeneratorRequest,
+    types: proto::AllTypes<'a>,
+    options: Options,
+    re: regex::Regex,
+}
+
+impl<'a> Expander<'a> {
+    /// Construct expander of directives referring to proto files and types of `request`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if descriptors in `request` are malformed or cannot be resolved.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn new(request: &'a CodeGeneratorRequest) -> Result<Self> {
+        let re = regex::Regex::new(r"\{\{#(proto|proto-message)\s+([^}]*)\}\}")
+            .expect("constructing regex");
+
+        Ok(Self {
+            request,
+            types: proto::get_types(request)?,
+            options: Options::default(),
+            re,
+        })
+    }
+
+    /// Replace all directives in `content` with the rendered documentation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a directive is malformed or refers to an unknown file, service or type.
+    pub fn expand(&self, content: &str) -> Result<String> {
+        let mut result = String::with_capacity(content.len());
+        let mut last = 0;
+
+        for captures in self.re.captures_iter(content) {
+            let (directive, [kind, args]) = captures.extract();
+            let args = args.split_whitespace().collect::<Vec<_>>();
+            let start = captures.get(0).map_or(0, |m| m.start());
+
+            result.push_str(&content[last..start]);
+
+            let expanded = match (kind, args.as_slice()) {
+                ("proto", [file]) => self.render_services(file, None)?,
+                ("proto", [file, service]) => self.render_services(file, Some(service))?,
+                ("proto-message", [name]) => self.render_type(name)?,
+                _ => return Err(anyhow!("malformed directive `{directive}`")),
+            };
+
+            result.push_str(&expanded);
+            last = start + directive.len();
+        }
+
+        result.push_str(&content[last..]);
+        Ok(result)
+    }
+
+    /// Render all services of `file` or just the one named `service`.
+    fn render_services(&self, file: &str, service: Option<&str>) -> Result<String> {
+        let services = proto::get_services(self.request, file, &self.types)?
+            .into_iter()
+            .filter(|s| service.is_none_or(|name| s.name == name))
+            .collect::<Vec<_>>();
+
+        if let (Some(name), true) = (service, services.is_empty()) {
+            return Err(anyhow!("service `{name}` not found in {file}"));
+        }
+
+        let page = render::Page::from(services, vec![], &self.types, &self.options)?;
+        Ok(page.render()?)
+    }
+
+    /// Render message or enum type with package-qualified `name`.
+    fn render_type(&self, name: &str) -> Result<String> {
+        let ty = self
+            .types
+            .get(&format!(".{name}"))
+            .ok_or_else(|| anyhow!("type `{name}` not found"))?;
+
+        let page = render::Page::from(vec![], vec![ty], &self.types, &self.options)?;
+        Ok(page.render()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Expander;
+    use crate::request_from_descriptor_set;
+    use prost_types::{
+        DescriptorProto, FileDescriptorProto, FileDescriptorSet, MethodDescriptorProto,
+        ServiceDescriptorProto, SourceCodeInfo,
+    };
+
+    #[test]
+    fn expand_directives() {
+        let request = request_from_descriptor_set(
+            FileDescriptorSet {
+                file: vec![FileDescriptorProto {
+                    name: Some("foo.proto".to_string()),
+                    package: Some("foo".to_string()),
+                    message_type: vec![DescriptorProto {
+                        name: Some("Empty".to_string()),
+                        ..Default::default()
+                    }],
+                    service: vec![ServiceDescriptorProto {
+                        name: Some("Service".to_string()),
+                        method: vec![MethodDescriptorProto {
+                            name: Some("Get".to_string()),
+                            input_type: Some(".foo.Empty".to_string()),
+                            output_type: Some(".foo.Empty".to_string()),
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    }],
+                    source_code_info: Some(SourceCodeInfo::default()),
+                    ..Default::default()
+                }],
+            },
+            vec![],
+        );
+
+        let expander = Expander::new(&request).unwrap();
+
+        let content = expander
+            .expand("Before\n{{#proto foo.proto Service}}\nAfter")
+            .unwrap();
+        assert!(content.starts_with("Before\n"));
+        assert!(content.contains("## foo.Service"));
+        assert!(content.contains("### `Get()`"));
+        assert!(content.ends_with("\nAfter"));
+
+        let content = expander.expand("{{#proto-message foo.Empty}}").unwrap();
+        assert!(content.contains("## foo.Empty"));
+        assert!(content.contains("message Empty {}"));
+
+        assert!(expander.expand("{{#proto foo.proto Missing}}").is_err());
+        assert!(expander.expand("{{#proto-message foo.Missing}}").is_err());
+    }
+}
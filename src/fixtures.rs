@@ -0,0 +1,44 @@
+//! Descriptors shared by the tests of the renderers.
+
+use prost_types::compiler::CodeGeneratorRequest;
+use prost_types::field_descriptor_proto::Type;
+use prost_types::{
+    DescriptorProto, FieldDescriptorProto, FileDescriptorProto, MethodDescriptorProto,
+    ServiceDescriptorProto, SourceCodeInfo,
+};
+
+/// Return request generating `foo.proto` with service `foo.Tree` whose method `Get` takes and
+/// returns the recursive message type `foo.Node`.
+pub fn tree() -> CodeGeneratorRequest {
+    CodeGeneratorRequest {
+        file_to_generate: vec!["foo.proto".to_string()],
+        proto_file: vec![FileDescriptorProto {
+            name: Some("foo.proto".to_string()),
+            package: Some("foo".to_string()),
+            message_type: vec![DescriptorProto {
+                name: Some("Node".to_string()),
+                field: vec![FieldDescriptorProto {
+                    name: Some("next".to_string()),
+                    number: Some(1),
+                    r#type: Some(Type::Message.into()),
+                    type_name: Some(".foo.Node".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            service: vec![ServiceDescriptorProto {
+                name: Some("Tree".to_string()),
+                method: vec![MethodDescriptorProto {
+                    name: Some("Get".to_string()),
+                    input_type: Some(".foo.Node".to_string()),
+                    output_type: Some(".foo.Node".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            source_code_info: Some(SourceCodeInfo::default()),
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
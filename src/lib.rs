@@ -0,0 +1,1388 @@
+//! Library interface of `protoc-gen-mdbook` to render mdBook pages in-process, e.g. from a
+//! `build.rs` script:
+//!
+//! ```no_run
+//! # fn main() -> anyhow::Result<()> {
+//! use prost::Message;
+//! use protoc_gen_mdbook::{generate, request_from_descriptor_set, Options};
+//!
+//! let set = prost_types::FileDescriptorSet::decode(&*std::fs::read("api.pb")?)?;
+//! let request = request_from_descriptor_set(set, vec![]);
+//! let options = Options::default().all_types(true).book_title("API");
+//!
+//! for file in generate(&request, &options)? {
+//!     std::fs::write(file.name(), file.content())?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::Result;
+use askama::Template;
+use prost_types::compiler::CodeGeneratorRequest;
+use prost_types::FileDescriptorSet;
+use render::links::{self, Links};
+use serde::Serialize;
+use std::collections::HashMap;
+
+pub use prost_types::compiler::code_generator_response::File;
+
+pub mod directives;
+#[cfg(test)]
+mod fixtures;
+mod model;
+mod proto;
+mod render;
+mod runtime;
+mod schema;
+
+/// Format of the generated files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// mdBook pages.
+    #[default]
+    Markdown,
+    /// Versioned JSON document of the documentation model.
+    Json,
+    /// Self-contained HTML pages.
+    Html,
+    /// AsciiDoc pages.
+    AsciiDoc,
+    /// reStructuredText pages for Sphinx with an `index.rst` listing them.
+    Rst,
+    /// MDX pages for Docusaurus in a directory per package.
+    Mdx,
+}
+
+impl Format {
+    /// Return file name extension of generated pages.
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Json => "json",
+            Self::Html => "html",
+            Self::AsciiDoc => "adoc",
+            Self::Rst => "rst",
+            Self::Mdx => "mdx",
+        }
+    }
+}
+
+/// Presentation of message and enum types on Markdown pages.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Style {
+    /// Declarations in `protobuf` code blocks.
+    #[default]
+    Code,
+    /// Tables of fields and enum values.
+    Table,
+}
+
+/// Placement of the message and enum types used by methods.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TypesSection {
+    /// Below each method using them, repeated for every method.
+    #[default]
+    Inline,
+    /// Once per page in the section of documented types.
+    Page,
+    /// Once per package on a page of its own, only for Markdown pages.
+    Package,
+}
+
+/// Syntax of the front matter prepended to pages for static site generators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrontMatter {
+    /// YAML between `---` lines as used by Jekyll and Hugo.
+    Yaml,
+    /// TOML between `+++` lines as used by Hugo.
+    Toml,
+}
+
+/// Options controlling the generated output, either parsed from the `mdbook_opt` parameter or
+/// built with the chainable setters.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    output: Option<String>,
+    optimize_for_doxygen: bool,
+    /// Document all top-level message and enum types, not just those used by services.
+    all_types: bool,
+    /// Generate a `SUMMARY.md` linking all pages.
+    summary: bool,
+    /// Title of the book if a `book.toml` should be generated.
+    book_title: Option<String>,
+    /// Directory with templates overriding the built-in ones.
+    template_dir: Option<String>,
+    format: Format,
+    /// Front matter prepended to each Markdown page.
+    front_matter: Option<FrontMatter>,
+    style: Style,
+    types_section: TypesSection,
+}
+
+impl Options {
+    /// Parse options from the `parameter` of `request`.
+    #[must_use]
+    pub fn from_request(request: &CodeGeneratorRequest) -> Self {
+        request
+            .parameter
+            .as_deref()
+            .map_or_else(Self::default, Self::from_parameter)
+    }
+
+    /// Parse options from a comma-separated list of `key:value` pairs as passed by `mdbook_opt`.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn from_parameter(parameter: &str) -> Self {
+        let re = regex::Regex::new(
+            r"(output|optimize|types|summary|book|template_dir|format|front_matter|style|types_section):([^,]+)",
+        )
+        .expect("constructing regex");
+
+        let mut result = Self::default();
+
+        for (_, [key, value]) in re.captures_iter(parameter).map(|m| m.extract()) {
+            if key == "output" {
+                result.output = Some(value.to_string());
+            } else if key == "optimize" {
+                result.optimize_for_doxygen = value == "doxygen";
+            } else if key == "types" {
+                result.all_types = value == "all";
+            } else if key == "summary" {
+                result.summary = value == "true";
+            } else if key == "book" {
+                result.book_title = Some(value.to_string());
+            } else if key == "template_dir" {
+                result.template_dir = Some(value.to_string());
+            } else if key == "format" {
+                result.format = match value {
+                    "json" => Format::Json,
+                    "html" => Format::Html,
+                    "asciidoc" => Format::AsciiDoc,
+                    "rst" => Format::Rst,
+                    "mdx" => Format::Mdx,
+                    _ => Format::Markdown,
+                };
+            } else if key == "front_matter" {
+                result.front_matter = match value {
+                    "yaml" => Some(FrontMatter::Yaml),
+                    "toml" => Some(FrontMatter::Toml),
+                    _ => None,
+                };
+            } else if key == "style" {
+                result.style = match value {
+                    "table" => Style::Table,
+                    _ => Style::Code,
+                };
+            } else if key == "types_section" {
+                result.types_section = match value {
+                    "page" => TypesSection::Page,
+                    "package" => TypesSection::Package,
+                    _ => TypesSection::Inline,
+                };
+            }
+        }
+
+        result
+    }
+
+    /// Render everything into a single file named `name` instead of one page per proto file.
+    #[must_use]
+    pub fn output(mut self, name: impl Into<String>) -> Self {
+        self.output = Some(name.into());
+        self
+    }
+
+    /// Fix header links for inclusion in Doxygen Markdown documentation.
+    #[must_use]
+    pub fn optimize_for_doxygen(mut self, yes: bool) -> Self {
+        self.optimize_for_doxygen = yes;
+        self
+    }
+
+    /// Document all top-level message and enum types, not just those used by services.
+    #[must_use]
+    pub fn all_types(mut self, yes: bool) -> Self {
+        self.all_types = yes;
+        self
+    }
+
+    /// Also generate a `SUMMARY.md` linking all pages.
+    #[must_use]
+    pub fn summary(mut self, yes: bool) -> Self {
+        self.summary = yes;
+        self
+    }
+
+    /// Also generate `SUMMARY.md`, `book.toml` and `protobuf.js` for a book titled `title`.
+    #[must_use]
+    pub fn book_title(mut self, title: impl Into<String>) -> Self {
+        self.book_title = Some(title.into());
+        self
+    }
+
+    /// Load templates from directory `dir`, using the built-in ones for those not found there.
+    #[must_use]
+    pub fn template_dir(mut self, dir: impl Into<String>) -> Self {
+        self.template_dir = Some(dir.into());
+        self
+    }
+
+    /// Generate files in `format` instead of mdBook pages.
+    #[must_use]
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Prepend front matter in `syntax` to each Markdown page generated for a proto file.
+    #[must_use]
+    pub fn front_matter(mut self, syntax: FrontMatter) -> Self {
+        self.front_matter = Some(syntax);
+        self
+    }
+
+    /// Render message and enum types on Markdown pages in `style`.
+    #[must_use]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Document the types used by methods in `section` instead of below each method.
+    #[must_use]
+    pub fn types_section(mut self, section: TypesSection) -> Self {
+        self.types_section = section;
+        self
+    }
+}
+
+/// Return name of the page documenting proto file `name`.
+fn page_name(name: &str, options: &Options) -> String {
+    options.output.clone().unwrap_or_else(|| {
+        let extension = options.format.extension();
+        format!("{}.{extension}", name.replace('/', "."))
+    })
+}
+
+/// Construct page documenting services and optionally all types of proto file `name`.
+fn build_page<'a>(
+    request: &'a CodeGeneratorRequest,
+    name: &str,
+    types: &'a proto::AllTypes,
+    options: &'a Options,
+) -> Result<render::Page<'a>> {
+    let services = proto::get_services(request, name, types)?;
+
+    let documented = if options.all_types {
+        proto::get_file_types(request, name, types)?
+    } else {
+        vec![]
+    };
+
+    let page = render::Page::from(services, documented, types, options)?;
+
+    // Types are left to the page of the package.
+    if package_types(options) {
+        Ok(page.without_types())
+    } else {
+        Ok(page)
+    }
+}
+
+/// Return `true` if types used by methods are documented on a Markdown page per package.
+fn package_types(options: &Options) -> bool {
+    options.format == Format::Markdown && options.types_section == TypesSection::Package
+}
+
+/// Page of the types declared in `package` which are documented on types pages.
+struct TypesPage<'a> {
+    package: &'a str,
+    /// Top-level types of the page, all declared in `package`.
+    declared: Vec<&'a proto::Types<'a>>,
+    page: render::Page<'a>,
+}
+
+/// Construct a types page per package declaring types used by services of the proto files to
+/// generate, or all of their types, in order of the first type of each package.
+///
+/// This includes packages without proto files to generate.
+fn build_types_pages<'a>(
+    request: &'a CodeGeneratorRequest,
+    types: &'a proto::AllTypes,
+    options: &'a Options,
+) -> Result<Vec<TypesPage<'a>>> {
+    if !package_types(options) {
+        return Ok(vec![]);
+    }
+
+    let mut packages = HashMap::new();
+
+    for file in &request.proto_file {
+        for ty in proto::get_file_types(request, file.name(), types)? {
+            for name in links::type_names(ty) {
+                packages.insert(name, file.package());
+            }
+        }
+    }
+
+    let mut services = vec![];
+    let mut documented = vec![];
+
+    for name in &request.file_to_generate {
+        services.append(&mut proto::get_services(request, name, types)?);
+
+        if options.all_types {
+            documented.append(&mut proto::get_file_types(request, name, types)?);
+        }
+    }
+
+    let mut declared: Vec<(&str, Vec<&proto::Types>)> = vec![];
+
+    for ty in render::Page::from(services, documented, types, options)?.types() {
+        let name = &ty.full_name()[1..];
+        let package = *packages
+            .get(name)
+            .ok_or_else(|| proto::Error::new(name, name, "type not declared in any proto file"))?;
+
+        match declared.iter_mut().find(|(p, _)| *p == package) {
+            Some((_, types)) => types.push(ty),
+            None => declared.push((package, vec![ty])),
+        }
+    }
+
+    declared
+        .into_iter()
+        .map(|(package, declared)| {
+            let page = render::Page::from(vec![], declared.clone(), types, options)?;
+
+            Ok(TypesPage {
+                package,
+                declared,
+                page,
+            })
+        })
+        .collect()
+}
+
+/// Return name of the page documenting the types of `package`.
+fn types_page_name(package: &str, options: &Options) -> String {
+    options.output.clone().unwrap_or_else(|| match package {
+        "" => "types.md".to_string(),
+        _ => format!("{package}.types.md"),
+    })
+}
+
+/// Render types page `types`, linking with `links`.
+fn render_types_page(types: TypesPage, links: &Links, options: &Options) -> Result<String> {
+    let page_name = types_page_name(types.package, options);
+    let page = types.page.with_links(links, &page_name);
+
+    match &options.template_dir {
+        Some(dir) => runtime::render(&page.model()?, options, dir),
+        None => Ok(page.render()?),
+    }
+}
+
+/// Collect types and methods documented on the Markdown or reStructuredText pages of all proto
+/// files to generate.
+fn collect_links(
+    request: &CodeGeneratorRequest,
+    types: &proto::AllTypes,
+    types_pages: &[TypesPage],
+    options: &Options,
+) -> Result<Links> {
+    let mut links = Links::default();
+
+    if !matches!(options.format, Format::Markdown | Format::Rst) {
+        return Ok(links);
+    }
+
+    for name in &request.file_to_generate {
+        let page = build_page(request, name, types, options)?;
+        let declared = proto::get_file_types(request, name, types)?;
+        links.add(&page_name(name, options), &page, &declared);
+    }
+
+    for types in types_pages {
+        links.add(
+            &types_page_name(types.package, options),
+            &types.page,
+            &types.declared,
+        );
+    }
+
+    Ok(links)
+}
+
+/// Render page of proto file `name` linking to types on other pages with `links`.
+fn render_page(
+    request: &CodeGeneratorRequest,
+    name: &str,
+    types: &proto::AllTypes,
+    links: &Links,
+    options: &Options,
+) -> Result<String> {
+    let page_name = page_name(name, options);
+    let page = build_page(request, name, types, options)?.with_links(links, &page_name);
+
+    match &options.template_dir {
+        Some(dir) => runtime::render(&page.model()?, options, dir),
+        None => Ok(page.render()?),
+    }
+}
+
+/// Render JSON document of all services and types of proto files `names`.
+fn render_json(
+    request: &CodeGeneratorRequest,
+    names: &[String],
+    types: &proto::AllTypes,
+) -> Result<String> {
+    let files = names
+        .iter()
+        .map(|name| {
+            let services = proto::get_services(request, name, types)?;
+            let declared = proto::get_file_types(request, name, types)?;
+
+            Ok(schema::File {
+                name,
+                package: proto::get_file(request, name)?.package(),
+                services: services.into_iter().map(schema::Service::from).collect(),
+                types: declared.into_iter().map(schema::Type::from).collect(),
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    let document = schema::Document {
+        version: schema::VERSION,
+        files,
+    };
+
+    Ok(serde_json::to_string_pretty(&document)? + "\n")
+}
+
+/// Return front matter of the Docusaurus page documenting proto files `names`.
+fn mdx_front_matter<'a>(
+    request: &'a CodeGeneratorRequest,
+    names: &[String],
+    title: &'a str,
+    options: &Options,
+) -> Result<render::mdx::FrontMatter<'a>> {
+    let source = options
+        .output
+        .as_deref()
+        .or(names.first().map(String::as_str))
+        .unwrap_or_default();
+
+    let stem = source
+        .strip_suffix(".proto")
+        .or_else(|| source.strip_suffix(".mdx"))
+        .unwrap_or(source);
+
+    let id = stem
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c,
+            _ => '-',
+        })
+        .collect();
+
+    // Pages are ordered as the proto files were passed to protoc.
+    let sidebar_position = names
+        .first()
+        .and_then(|name| request.file_to_generate.iter().position(|f| f == name))
+        .map_or(1, |index| index + 1);
+
+    let mut tags = vec![];
+
+    for name in names {
+        let package = proto::get_file(request, name)?.package();
+
+        if !package.is_empty() && !tags.contains(&package) {
+            tags.push(package);
+        }
+    }
+
+    Ok(render::mdx::FrontMatter {
+        id,
+        title,
+        sidebar_position,
+        tags,
+    })
+}
+
+/// Render single document of proto files `names` titled with the book title or the file names,
+/// labelling in reStructuredText only the types `links` resolves to this document.
+fn render_document(
+    request: &CodeGeneratorRequest,
+    names: &[String],
+    types: &proto::AllTypes,
+    links: &Links,
+    options: &Options,
+) -> Result<String> {
+    let mut services = vec![];
+    let mut documented = vec![];
+
+    for name in names {
+        services.append(&mut proto::get_services(request, name, types)?);
+
+        if options.all_types {
+            documented.append(&mut proto::get_file_types(request, name, types)?);
+        }
+    }
+
+    let page_name = names
+        .first()
+        .map(|name| page_name(name, options))
+        .unwrap_or_default();
+    let page =
+        render::Page::from(services, documented, types, options)?.with_links(links, &page_name);
+    // Sphinx and Docusaurus list pages with their own titles, the book title is left to the index.
+    let title = match (&options.book_title, options.format) {
+        (Some(title), Format::Html | Format::AsciiDoc) => title.clone(),
+        _ => names.join(", "),
+    };
+
+    match options.format {
+        Format::Html => Ok(render::html::render(&page, &title)?),
+        Format::Rst => Ok(render::rst::render(&page, &title)?),
+        Format::Mdx => {
+            let front_matter = mdx_front_matter(request, names, &title, options)?;
+            Ok(render::mdx::render(&page, &front_matter)?)
+        }
+        _ => Ok(render::asciidoc::render(&page, &title)?),
+    }
+}
+
+/// Render documentation of proto files `names` as content of a single file, with Markdown and
+/// reStructuredText pages linking to each other with `links`.
+fn render_files(
+    request: &CodeGeneratorRequest,
+    names: &[String],
+    types: &proto::AllTypes,
+    links: &Links,
+    options: &Options,
+) -> Result<String> {
+    match options.format {
+        Format::Markdown => {
+            let mut content = String::new();
+
+            for name in names {
+                content.push_str(&render_page(request, name, types, links, options)?);
+            }
+
+            Ok(content)
+        }
+        Format::Json => render_json(request, names, types),
+        Format::Html | Format::AsciiDoc | Format::Rst | Format::Mdx => {
+            render_document(request, names, types, links, options)
+        }
+    }
+}
+
+/// Generate single page named `name` containing all services from all proto files.
+fn generate_single_page(
+    request: &CodeGeneratorRequest,
+    types: &proto::AllTypes,
+    types_pages: Vec<TypesPage>,
+    options: &Options,
+) -> Result<Vec<File>> {
+    let links = collect_links(request, types, &types_pages, options)?;
+    let mut content = render_files(request, &request.file_to_generate, types, &links, options)?;
+
+    for types in types_pages {
+        content.push_str(&render_types_page(types, &links, options)?);
+    }
+
+    Ok(vec![File {
+        name: options.output.clone(),
+        content: Some(content),
+        ..Default::default()
+    }])
+}
+
+/// Render front matter in `syntax` for the page of proto file `name` ordered by `weight`.
+fn render_front_matter(
+    request: &CodeGeneratorRequest,
+    name: &str,
+    weight: usize,
+    syntax: FrontMatter,
+) -> Result<String> {
+    let file = proto::get_file(request, name)?;
+    let package = file.package();
+
+    let services: Vec<_> = file
+        .service
+        .iter()
+        .map(|service| match package {
+            "" => service.name().to_string(),
+            _ => format!("{package}.{}", service.name()),
+        })
+        .collect();
+
+    // A file is deprecated as a whole or if all of its services are.
+    let deprecated = file.options.as_ref().is_some_and(|o| o.deprecated())
+        || (!file.service.is_empty()
+            && file
+                .service
+                .iter()
+                .all(|service| service.options.as_ref().is_some_and(|o| o.deprecated())));
+
+    // Pages of files with a single service are named after it.
+    let title = match services.as_slice() {
+        [service] => service.clone(),
+        _ => name.to_string(),
+    };
+
+    let front_matter = render::FrontMatter {
+        toml: syntax == FrontMatter::Toml,
+        title,
+        weight,
+        package,
+        deprecated,
+        services,
+    };
+
+    Ok(front_matter.render()?)
+}
+
+/// Generate pages for each proto file containing all service documentations of that proto file.
+fn generate_multiple_pages(
+    request: &CodeGeneratorRequest,
+    types: &proto::AllTypes,
+    types_pages: Vec<TypesPage>,
+    options: &Options,
+) -> Result<Vec<File>> {
+    let links = collect_links(request, types, &types_pages, options)?;
+
+    let mut pages = request
+        .file_to_generate
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let names = std::slice::from_ref(name);
+            let mut content = render_files(request, names, types, &links, options)?;
+            let mut page = page_name(name, options);
+
+            if let (Some(syntax), Format::Markdown) = (options.front_matter, options.format) {
+                content.insert_str(0, &render_front_matter(request, name, index + 1, syntax)?);
+            }
+
+            // Docusaurus groups the pages of each package in a sidebar category.
+            if options.format == Format::Mdx {
+                let package = proto::get_file(request, name)?.package();
+
+                if !package.is_empty() {
+                    page = format!("{package}/{page}");
+                }
+            }
+
+            Ok(File {
+                name: Some(page),
+                content: Some(content),
+                ..Default::default()
+            })
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()?;
+
+    for types in types_pages {
+        pages.push(File {
+            name: Some(types_page_name(types.package, options)),
+            content: Some(render_types_page(types, &links, options)?),
+            ..Default::default()
+        });
+    }
+
+    Ok(pages)
+}
+
+/// Generate `SUMMARY.md` listing the types pages of `packages` and if requested `book.toml` and the
+/// highlight.js protobuf definition.
+fn generate_book(
+    request: &CodeGeneratorRequest,
+    packages: &[&str],
+    options: &Options,
+) -> Result<Vec<File>> {
+    let mut chapters = request
+        .file_to_generate
+        .iter()
+        .map(|name| {
+            let file = proto::get_file(request, name)?;
+
+            let services = file
+                .service
+                .iter()
+                .map(|service| {
+                    let methods = service.method.iter().map(|m| m.name()).collect();
+                    (
+                        proto::qualified_name(file.package(), service.name()),
+                        methods,
+                    )
+                })
+                .collect();
+
+            Ok(render::Chapter {
+                file: name,
+                package: file.package(),
+                page: page_name(name, options),
+                services,
+            })
+        })
+        .collect::<Result<Vec<_>, proto::Error>>()?;
+
+    // The summary lists the types of each package after its files.
+    for package in packages {
+        chapters.push(render::Chapter {
+            file: "Types",
+            package,
+            page: types_page_name(package, options),
+            services: vec![],
+        });
+    }
+
+    let mut files = vec![File {
+        name: Some("SUMMARY.md".to_string()),
+        content: Some(render::Summary::from(chapters).render()? + "\n"),
+        ..Default::default()
+    }];
+
+    if let Some(title) = &options.book_title {
+        files.push(File {
+            name: Some("book.toml".to_string()),
+            content: Some(render::Book { title }.render()? + "\n"),
+            ..Default::default()
+        });
+
+        files.push(File {
+            name: Some("protobuf.js".to_string()),
+            content: Some(render::HIGHLIGHT_JS.to_string()),
+            ..Default::default()
+        });
+    }
+
+    Ok(files)
+}
+
+/// Generate Sphinx `index.rst` with a table of contents linking all generated `pages`.
+fn generate_index(pages: &[File], options: &Options) -> Result<File> {
+    let documents = pages
+        .iter()
+        .map(|page| page.name().strip_suffix(".rst").unwrap_or(page.name()))
+        .collect();
+
+    let index = render::rst::Index {
+        title: options.book_title.as_deref().unwrap_or("API Reference"),
+        documents,
+    };
+
+    Ok(File {
+        name: Some("index.rst".to_string()),
+        content: Some(index.render()?),
+        ..Default::default()
+    })
+}
+
+/// Generate Docusaurus `_category_.json` labeling the directory of each package.
+fn generate_categories(request: &CodeGeneratorRequest) -> Result<Vec<File>> {
+    let mut packages = vec![];
+
+    for name in &request.file_to_generate {
+        let package = proto::get_file(request, name)?.package();
+
+        if !package.is_empty() && !packages.contains(&package) {
+            packages.push(package);
+        }
+    }
+
+    packages
+        .into_iter()
+        .map(|package| {
+            let category = serde_json::json!({
+                "label": package,
+                "link": { "type": "generated-index" },
+            });
+
+            Ok(File {
+                name: Some(format!("{package}/_category_.json")),
+                content: Some(serde_json::to_string_pretty(&category)? + "\n"),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Generate all files for the proto files to generate of `request` according to `options`.
+///
+/// # Errors
+///
+/// Returns an error if descriptors in `request` are malformed or cannot be resolved.
+pub fn generate(request: &CodeGeneratorRequest, options: &Options) -> Result<Vec<File>> {
+    let types = proto::get_types(request)?;
+    let types_pages = build_types_pages(request, &types, options)?;
+    let packages: Vec<_> = types_pages.iter().map(|types| types.package).collect();
+
+    let mut files = if options.output.is_some() {
+        generate_single_page(request, &types, types_pages, options)?
+    } else {
+        generate_multiple_pages(request, &types, types_pages, options)?
+    };
+
+    let book = options.summary || options.book_title.is_some();
+
+    if book && options.format == Format::Markdown {
+        files.append(&mut generate_book(request, &packages, options)?);
+    }
+
+    if options.format == Format::Rst && files.iter().all(|file| file.name() != "index.rst") {
+        files.push(generate_index(&files, options)?);
+    }
+
+    if options.format == Format::Mdx && options.output.is_none() {
+        files.append(&mut generate_categories(request)?);
+    }
+
+    Ok(files)
+}
+
+/// Construct request equivalent to a protoc invocation generating `files` of `set` or all files
+/// if `files` is empty.
+#[must_use]
+pub fn request_from_descriptor_set(
+    set: FileDescriptorSet,
+    files: Vec<String>,
+) -> CodeGeneratorRequest {
+    let file_to_generate = if files.is_empty() {
+        set.file.iter().map(|f| f.name().to_string()).collect()
+    } else {
+        files
+    };
+
+    CodeGeneratorRequest {
+        file_to_generate,
+        proto_file: set.file,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_types::field_descriptor_proto::{Label, Type};
+    use prost_types::{
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, MethodDescriptorProto,
+        ServiceDescriptorProto, ServiceOptions, SourceCodeInfo,
+    };
+
+    #[test]
+    fn parse_empty_options() {
+        let request = CodeGeneratorRequest {
+            parameter: None,
+            ..Default::default()
+        };
+
+        let options = Options::from_request(&request);
+        assert!(options.output.is_none());
+        assert!(!options.optimize_for_doxygen);
+    }
+
+    #[test]
+    fn parse_single_file_options() {
+        let request = CodeGeneratorRequest {
+            parameter: Some("output:foo.md".to_string()),
+            ..Default::default()
+        };
+
+        let options = Options::from_request(&request);
+        assert!(options.output.is_some());
+        assert_eq!(options.output.unwrap(), "foo.md");
+        assert!(!options.optimize_for_doxygen);
+    }
+
+    #[test]
+    fn parse_optimize_for_doxygen() {
+        let request = CodeGeneratorRequest {
+            parameter: Some("optimize:doxygen".to_string()),
+            ..Default::default()
+        };
+
+        let options = Options::from_request(&request);
+        assert!(options.output.is_none());
+        assert!(options.optimize_for_doxygen);
+    }
+
+    #[test]
+    fn parse_both_options() {
+        let request = CodeGeneratorRequest {
+            parameter: Some("output:bar.md,optimize:doxygen".to_string()),
+            ..Default::default()
+        };
+
+        let options = Options::from_request(&request);
+        assert!(options.output.is_some());
+        assert_eq!(options.output.unwrap(), "bar.md");
+        assert!(options.optimize_for_doxygen);
+    }
+
+    #[test]
+    fn parse_all_types() {
+        let request = CodeGeneratorRequest {
+            parameter: Some("types:all".to_string()),
+            ..Default::default()
+        };
+
+        let options = Options::from_request(&request);
+        assert!(options.output.is_none());
+        assert!(options.all_types);
+    }
+
+    #[test]
+    fn parse_book_options() {
+        let request = CodeGeneratorRequest {
+            parameter: Some("summary:true,book:My API".to_string()),
+            ..Default::default()
+        };
+
+        let options = Options::from_request(&request);
+        assert!(options.summary);
+        assert_eq!(options.book_title.unwrap(), "My API");
+    }
+
+    #[test]
+    fn build_options() {
+        let options = Options::default()
+            .output("api.md")
+            .all_types(true)
+            .book_title("API");
+
+        assert_eq!(options.output.unwrap(), "api.md");
+        assert!(!options.optimize_for_doxygen);
+        assert!(options.all_types);
+        assert!(!options.summary);
+        assert_eq!(options.book_title.unwrap(), "API");
+    }
+
+    #[test]
+    fn parse_format() {
+        let request = CodeGeneratorRequest {
+            parameter: Some("format:json".to_string()),
+            ..Default::default()
+        };
+
+        let options = Options::from_request(&request);
+        assert_eq!(options.format, Format::Json);
+        assert_eq!(page_name("a/b.proto", &options), "a.b.proto.json");
+    }
+
+    #[test]
+    fn generate_all_types() {
+        let request = CodeGeneratorRequest {
+            file_to_generate: vec!["foo.proto".to_string()],
+            proto_file: vec![FileDescriptorProto {
+                name: Some("foo.proto".to_string()),
+                package: Some("foo".to_string()),
+                message_type: vec![DescriptorProto {
+                    name: Some("Bar".to_string()),
+                    ..Default::default()
+                }],
+                source_code_info: Some(SourceCodeInfo::default()),
+                ..Default::default()
+            }],
+            parameter: Some("types:all".to_string()),
+            ..Default::default()
+        };
+
+        let files = generate(&request, &Options::from_request(&request)).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name(), "foo.proto.md");
+        assert!(files[0].content().contains("## foo.Bar\n"));
+        assert!(files[0]
+            .content()
+            .contains("<pre><code class=\"language-protobuf\">message Bar {}</code></pre>"));
+
+        // Files without services are empty unless all types are documented.
+        let files = generate(&request, &Options::default()).unwrap();
+        assert!(!files[0].content().contains("foo.Bar"));
+    }
+
+    #[test]
+    fn generate_without_source_info() {
+        let set = FileDescriptorSet {
+            file: vec![FileDescriptorProto {
+                name: Some("foo.proto".to_string()),
+                package: Some("foo".to_string()),
+                message_type: vec![DescriptorProto {
+                    name: Some("Bar".to_string()),
+                    ..Default::default()
+                }],
+                service: vec![ServiceDescriptorProto {
+                    name: Some("Svc".to_string()),
+                    method: vec![MethodDescriptorProto {
+                        name: Some("Get".to_string()),
+                        input_type: Some(".foo.Bar".to_string()),
+                        output_type: Some(".foo.Bar".to_string()),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+
+        let request = request_from_descriptor_set(set, vec![]);
+        let files = generate(&request, &Options::default()).unwrap();
+        assert_eq!(files[0].name(), "foo.proto.md");
+        assert!(files[0].content().contains("## foo.Svc\n"));
+        assert!(files[0].content().contains("message Bar {}"));
+    }
+
+    #[test]
+    fn generate_json() {
+        let request = CodeGeneratorRequest {
+            file_to_generate: vec!["foo.proto".to_string()],
+            proto_file: vec![FileDescriptorProto {
+                name: Some("foo.proto".to_string()),
+                package: Some("foo".to_string()),
+                message_type: vec![DescriptorProto {
+                    name: Some("Bar".to_string()),
+                    field: vec![FieldDescriptorProto {
+                        name: Some("baz".to_string()),
+                        number: Some(1),
+                        label: Some(Label::Repeated.into()),
+                        r#type: Some(Type::Message.into()),
+                        type_name: Some(".foo.Bar".to_string()),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                service: vec![ServiceDescriptorProto {
+                    name: Some("Svc".to_string()),
+                    method: vec![MethodDescriptorProto {
+                        name: Some("Get".to_string()),
+                        input_type: Some(".foo.Bar".to_string()),
+                        output_type: Some(".foo.Bar".to_string()),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                source_code_info: Some(SourceCodeInfo::default()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let options = Options::default().format(Format::Json).summary(true);
+        let files = generate(&request, &options).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name(), "foo.proto.json");
+
+        let document: serde_json::Value = serde_json::from_str(files[0].content()).unwrap();
+        assert_eq!(document["version"], 1);
+        assert_eq!(document["files"][0]["package"], "foo");
+
+        let get = &document["files"][0]["services"][0]["methods"][0];
+        assert_eq!(get["call_type"], "unary");
+        assert_eq!(get["input_type"], "foo.Bar");
+        assert!(get.get("input_types").is_none());
+
+        let bar = &document["files"][0]["types"][0];
+        assert_eq!(bar["kind"], "message");
+        assert_eq!(bar["full_name"], "foo.Bar");
+        assert_eq!(bar["fields"][0]["type"], "Bar");
+        assert_eq!(bar["fields"][0]["type_names"][0], "foo.Bar");
+        assert_eq!(bar["fields"][0]["repeated"], true);
+
+        // Only declarations are serialized, not how and where they are rendered.
+        assert!(bar.get("definition").is_none());
+        assert!(bar.get("anchors").is_none());
+        assert!(bar["fields"][0].get("recursive").is_none());
+    }
+
+    #[test]
+    fn generate_mdx() {
+        let file = |name: &str, package: Option<&str>| FileDescriptorProto {
+            name: Some(name.to_string()),
+            package: package.map(ToString::to_string),
+            message_type: vec![DescriptorProto {
+                name: Some("Bar".to_string()),
+                ..Default::default()
+            }],
+            source_code_info: Some(SourceCodeInfo::default()),
+            ..Default::default()
+        };
+
+        let request = CodeGeneratorRequest {
+            file_to_generate: vec!["api/foo.proto".to_string(), "bar.proto".to_string()],
+            proto_file: vec![
+                file("api/foo.proto", Some("foo.v1")),
+                file("bar.proto", None),
+            ],
+            ..Default::default()
+        };
+
+        let options = Options::default().format(Format::Mdx).all_types(true);
+        let files = generate(&request, &options).unwrap();
+        let names: Vec<_> = files.iter().map(File::name).collect();
+
+        assert_eq!(
+            names,
+            [
+                "foo.v1/api.foo.proto.mdx",
+                "bar.proto.mdx",
+                "foo.v1/_category_.json"
+            ]
+        );
+        assert!(files[0].content().starts_with(
+            "---\nid: api-foo\ntitle: \"api/foo.proto\"\nsidebar_position: 1\n\
+             tags:\n  - \"foo.v1\"\n---\n\n## foo.v1.Bar\n"
+        ));
+        assert!(files[1]
+            .content()
+            .starts_with("---\nid: bar\ntitle: \"bar.proto\"\nsidebar_position: 2\n---\n"));
+
+        let category: serde_json::Value = serde_json::from_str(files[2].content()).unwrap();
+        assert_eq!(category["label"], "foo.v1");
+    }
+
+    #[test]
+    fn generate_front_matter() {
+        let service = |name: &str, deprecated| ServiceDescriptorProto {
+            name: Some(name.to_string()),
+            method: vec![MethodDescriptorProto {
+                name: Some("Get".to_string()),
+                input_type: Some(".foo.Bar".to_string()),
+                output_type: Some(".foo.Bar".to_string()),
+                ..Default::default()
+            }],
+            options: Some(ServiceOptions {
+                deprecated: Some(deprecated),
+                ..Default::default()
+            }),
+        };
+
+        let request = CodeGeneratorRequest {
+            file_to_generate: vec!["a.proto".to_string(), "b.proto".to_string()],
+            proto_file: vec![
+                FileDescriptorProto {
+                    name: Some("a.proto".to_string()),
+                    package: Some("foo".to_string()),
+                    message_type: vec![DescriptorProto {
+                        name: Some("Bar".to_string()),
+                        ..Default::default()
+                    }],
+                    service: vec![service("Old", true), service("New", false)],
+                    source_code_info: Some(SourceCodeInfo::default()),
+                    ..Default::default()
+                },
+                FileDescriptorProto {
+                    name: Some("b.proto".to_string()),
+                    package: Some("foo".to_string()),
+                    dependency: vec!["a.proto".to_string()],
+                    service: vec![service("Legacy", true)],
+                    source_code_info: Some(SourceCodeInfo::default()),
+                    ..Default::default()
+                },
+            ],
+            parameter: Some("front_matter:yaml".to_string()),
+            ..Default::default()
+        };
+
+        let files = generate(&request, &Options::from_request(&request)).unwrap();
+        assert!(files[0].content().starts_with(
+            "---\ntitle: \"a.proto\"\nweight: 1\npackage: \"foo\"\ndeprecated: false\n\
+             services:\n  - \"foo.Old\"\n  - \"foo.New\"\n---\n"
+        ));
+
+        let options = Options::default().front_matter(FrontMatter::Toml);
+        let files = generate(&request, &options).unwrap();
+        assert!(files[1].content().starts_with(
+            "+++\ntitle = \"foo.Legacy\"\nweight = 2\npackage = \"foo\"\n\
+             deprecated = true\nservices = [\"foo.Legacy\"]\n+++\n"
+        ));
+    }
+
+    #[test]
+    fn generate_shared_types() {
+        let method = |name: &str| MethodDescriptorProto {
+            name: Some(name.to_string()),
+            input_type: Some(".foo.Bar".to_string()),
+            output_type: Some(".foo.Bar".to_string()),
+            ..Default::default()
+        };
+
+        let request = CodeGeneratorRequest {
+            file_to_generate: vec!["a.proto".to_string()],
+            proto_file: vec![FileDescriptorProto {
+                name: Some("a.proto".to_string()),
+                package: Some("foo".to_string()),
+                message_type: vec![DescriptorProto {
+                    name: Some("Bar".to_string()),
+                    ..Default::default()
+                }],
+                service: vec![ServiceDescriptorProto {
+                    name: Some("Svc".to_string()),
+                    method: vec![method("Get"), method("Put")],
+                    ..Default::default()
+                }],
+                source_code_info: Some(SourceCodeInfo::default()),
+                ..Default::default()
+            }],
+            parameter: Some("types_section:page".to_string()),
+            ..Default::default()
+        };
+
+        let files = generate(&request, &Options::from_request(&request)).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].content().matches("message Bar").count(), 1);
+        assert!(files[0]
+            .content()
+            .contains("**Input:** [`foo.Bar`](#foo.Bar)"));
+
+        let options = Options::default()
+            .types_section(TypesSection::Package)
+            .summary(true);
+        let files = generate(&request, &options).unwrap();
+        let names: Vec<_> = files.iter().map(File::name).collect();
+        assert_eq!(names, ["a.proto.md", "foo.types.md", "SUMMARY.md"]);
+        assert!(!files[0].content().contains("message Bar"));
+        assert!(files[0].content().contains("(foo.types.md#foo.Bar)"));
+        assert_eq!(files[1].content().matches("message Bar").count(), 1);
+        assert!(files[1].content().contains("(a.proto.md#foo.Svc.Put)"));
+        assert!(files[2].content().contains("  - [Types](foo.types.md)"));
+    }
+
+    #[test]
+    fn generate_method_index() {
+        let service = |name: &str| ServiceDescriptorProto {
+            name: Some(name.to_string()),
+            method: ["Get", "Put", "Delete"]
+                .iter()
+                .map(|method| MethodDescriptorProto {
+                    name: Some(method.to_string()),
+                    input_type: Some(".foo.Bar".to_string()),
+                    output_type: Some(".foo.Bar".to_string()),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let request = CodeGeneratorRequest {
+            file_to_generate: vec!["a.proto".to_string()],
+            proto_file: vec![FileDescriptorProto {
+                name: Some("a.proto".to_string()),
+                package: Some("foo".to_string()),
+                message_type: vec![DescriptorProto {
+                    name: Some("Bar".to_string()),
+                    ..Default::default()
+                }],
+                service: vec![service("A"), service("B")],
+                source_code_info: Some(SourceCodeInfo::default()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // Methods of both services share their names, so they are indexed by their anchors.
+        let files = generate(&request, &Options::default()).unwrap();
+        assert!(files[0]
+            .content()
+            .contains("<a href=\"#foo.B.Get\">`Get()`</a>"));
+
+        let files = generate(&request, &Options::default().format(Format::Mdx)).unwrap();
+        assert!(files[0].content().contains("<a id=\"foo.B.Get\"></a>"));
+        assert!(files[0].content().contains("- [`Get()`](#foo.B.Get)"));
+    }
+
+    /// Return request with `common.proto` declaring `common.Status` returned by the services of
+    /// `a.proto` and `b.proto` in packages `a` and `b`.
+    fn shared_status(file_to_generate: &[&str]) -> CodeGeneratorRequest {
+        let file = |name: &str, package: &str| FileDescriptorProto {
+            name: Some(format!("{name}.proto")),
+            package: Some(package.to_string()),
+            dependency: vec!["common.proto".to_string()],
+            service: vec![ServiceDescriptorProto {
+                name: Some("Svc".to_string()),
+                method: vec![MethodDescriptorProto {
+                    name: Some("Get".to_string()),
+                    input_type: Some(".common.Status".to_string()),
+                    output_type: Some(".common.Status".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            source_code_info: Some(SourceCodeInfo::default()),
+            ..Default::default()
+        };
+
+        CodeGeneratorRequest {
+            file_to_generate: file_to_generate
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            proto_file: vec![
+                FileDescriptorProto {
+                    name: Some("common.proto".to_string()),
+                    package: Some("common".to_string()),
+                    message_type: vec![DescriptorProto {
+                        name: Some("Status".to_string()),
+                        ..Default::default()
+                    }],
+                    source_code_info: Some(SourceCodeInfo::default()),
+                    ..Default::default()
+                },
+                file("a", "a"),
+                file("b", "b"),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn generate_rst_labels() {
+        let label = ".. _common.Status:";
+        let options = Options::default().format(Format::Rst).all_types(true);
+
+        let request = shared_status(&["a.proto", "b.proto"]);
+        let files = generate(&request, &options).unwrap();
+        assert_eq!(files[0].content().matches(label).count(), 1);
+        assert!(!files[1].content().contains(label));
+        assert!(files[1]
+            .content()
+            .contains("**Input:** :ref:`common.Status <common.Status>`"));
+
+        let request = shared_status(&["a.proto", "b.proto", "common.proto"]);
+        let files = generate(&request, &options).unwrap();
+        assert!(!files[0].content().contains(label));
+        assert!(!files[1].content().contains(label));
+        assert_eq!(files[2].content().matches(label).count(), 1);
+    }
+
+    #[test]
+    fn generate_types_of_declaring_package() {
+        let request = shared_status(&["a.proto", "b.proto"]);
+        let options = Options::default()
+            .types_section(TypesSection::Package)
+            .summary(true);
+
+        let files = generate(&request, &options).unwrap();
+        let names: Vec<_> = files.iter().map(File::name).collect();
+        assert_eq!(
+            names,
+            ["a.proto.md", "b.proto.md", "common.types.md", "SUMMARY.md"]
+        );
+        assert!(files[0]
+            .content()
+            .contains("**Input:** [`common.Status`](common.types.md#common.Status)"));
+        assert!(files[1]
+            .content()
+            .contains("**Input:** [`common.Status`](common.types.md#common.Status)"));
+        assert_eq!(files[2].content().matches("message Status").count(), 1);
+        assert!(files[2].content().contains("(a.proto.md#a.Svc.Get)"));
+        assert!(files[2].content().contains("(b.proto.md#b.Svc.Get)"));
+        assert!(files[3]
+            .content()
+            .contains("- [common]()\n  - [Types](common.types.md)"));
+    }
+}
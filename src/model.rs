@@ -0,0 +1,214 @@
+//! Serializable documentation model passed to user-supplied templates.
+//!
+//! It mirrors what the built-in templates see in `render::Page`, `render::Service`,
+//! `render::Method` and `proto::Types`, with names qualified without the leading dot.
+
+use crate::proto;
+use serde::Serialize;
+
+/// Everything documented on one page.
+#[derive(Serialize)]
+pub struct Page<'a> {
+    pub services: Vec<Service<'a>>,
+    /// Types documented on their own regardless of their use in services.
+    pub types: Vec<Type<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct Service<'a> {
+    pub name: &'a str,
+    pub package: &'a str,
+    /// Package-qualified name or just the name if there is no package.
+    pub full_name: String,
+    pub description: &'a str,
+    pub deprecated: bool,
+    /// Methods which are not deprecated.
+    pub methods: Vec<Method<'a>>,
+    pub deprecated_methods: Vec<Method<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct Method<'a> {
+    pub name: &'a str,
+    /// Anchor of the method, its package-qualified name.
+    pub id: String,
+    /// One of `unary`, `client streaming`, `server streaming` or `bidi streaming`.
+    pub call_type: String,
+    pub description: &'a str,
+    pub deprecated: bool,
+    /// Package-qualified name of the input type.
+    pub input_name: &'a str,
+    /// Package-qualified name of the output type.
+    pub output_name: &'a str,
+    /// Link to the documentation of the input type, on this or another page.
+    pub input_url: String,
+    /// Link to the documentation of the output type, on this or another page.
+    pub output_url: String,
+    /// Input type followed by all types reachable from its fields.
+    pub input_types: Vec<Type<'a>>,
+    /// Output type followed by all types reachable from its fields.
+    pub output_types: Vec<Type<'a>>,
+}
+
+/// Message or enum type, distinguished by `kind` being `message` or `enum`.
+#[derive(Serialize)]
+pub struct Type<'a> {
+    #[serde(flatten)]
+    pub kind: Kind<'a>,
+    /// Declaration as plain text for code blocks.
+    pub definition: String,
+    /// Declaration as HTML with field types linking to their types, rendered by the built-in
+    /// template without the enclosing `<pre>` block.
+    pub linked_definition: String,
+    /// Tables of the fields of the message and its nested types or of the enum values, carrying
+    /// the anchors of the types, only with `style:table`.
+    pub table: Option<String>,
+    /// Anchors of this and its nested types if they are declared here for the first time.
+    pub anchors: Vec<String>,
+    /// Methods and message types using this type on any page.
+    pub used_by: Vec<Link>,
+}
+
+/// Element linked to by name.
+#[derive(Serialize)]
+pub struct Link {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Kind<'a> {
+    Message(Message<'a>),
+    Enum(Enum<'a>),
+}
+
+#[derive(Serialize)]
+pub struct Message<'a> {
+    pub name: &'a str,
+    pub full_name: &'a str,
+    pub description: &'a str,
+    /// Fields ordered by number, including those of `oneofs`.
+    pub fields: Vec<Field<'a>>,
+    pub oneofs: Vec<Oneof<'a>>,
+    pub nested: Vec<Message<'a>>,
+    pub enums: Vec<Enum<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct Field<'a> {
+    pub name: &'a str,
+    /// Type as declared, e.g. `string`, `Foo` or `map<string, Foo>`.
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// Package-qualified names of the message and enum types in `ty`.
+    pub type_names: Vec<&'a str>,
+    pub number: i32,
+    pub optional: bool,
+    pub repeated: bool,
+    /// Name of the enclosing `oneof`.
+    pub oneof: Option<&'a str>,
+    /// `true` if the field type is listed above and encloses the field, closing a cycle.
+    pub recursive: bool,
+    pub leading_comments: &'a str,
+    pub trailing_comments: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct Oneof<'a> {
+    pub name: &'a str,
+    pub leading_comments: &'a str,
+    pub trailing_comments: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct Enum<'a> {
+    pub name: &'a str,
+    pub full_name: &'a str,
+    pub description: &'a str,
+    pub values: Vec<EnumValue<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct EnumValue<'a> {
+    pub name: &'a str,
+    pub number: i32,
+    pub leading_comments: &'a str,
+    pub trailing_comments: &'a str,
+}
+
+impl<'a> Message<'a> {
+    /// Convert `ty` and its nested types, flagging fields listed in `recursive`.
+    pub fn from(ty: &'a proto::MessageType<'a>, recursive: &[&proto::Field]) -> Self {
+        let fields = ty
+            .fields
+            .iter()
+            .map(|field| Field {
+                name: field.name,
+                ty: field.ty.to_string(),
+                type_names: field
+                    .ty
+                    .custom_types()
+                    .iter()
+                    .map(|custom| &custom.name.original[1..])
+                    .collect(),
+                number: field.number,
+                optional: field.optional,
+                repeated: field.repeated,
+                oneof: field.oneof_index.and_then(|index| {
+                    ty.oneofs
+                        .iter()
+                        .find(|oneof| oneof.index == index)
+                        .map(|oneof| oneof.name)
+                }),
+                recursive: recursive.iter().any(|f| std::ptr::eq(*f, field)),
+                leading_comments: field.leading_comments,
+                trailing_comments: field.trailing_comments,
+            })
+            .collect();
+
+        let oneofs = ty
+            .oneofs
+            .iter()
+            .map(|oneof| Oneof {
+                name: oneof.name,
+                leading_comments: oneof.leading_comments,
+                trailing_comments: oneof.trailing_comments,
+            })
+            .collect();
+
+        Self {
+            name: ty.name,
+            full_name: &ty.full_name[1..],
+            description: ty.description,
+            fields,
+            oneofs,
+            nested: ty
+                .nested
+                .iter()
+                .map(|nested| Self::from(nested, recursive))
+                .collect(),
+            enums: ty.enums.iter().map(Enum::from).collect(),
+        }
+    }
+}
+
+impl<'a> From<&'a proto::EnumType<'a>> for Enum<'a> {
+    fn from(ty: &'a proto::EnumType<'a>) -> Self {
+        Self {
+            name: ty.name,
+            full_name: &ty.full_name[1..],
+            description: ty.description,
+            values: ty
+                .values
+                .iter()
+                .map(|value| EnumValue {
+                    name: value.name,
+                    number: value.number,
+                    leading_comments: value.leading_comments,
+                    trailing_comments: value.trailing_comments,
+                })
+                .collect(),
+        }
+    }
+}
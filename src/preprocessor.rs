@@ -0,0 +1,71 @@
+//! mdBook preprocessor expanding `{{#proto ...}}` directives in hand-written chapters.
+//!
+//! The descriptors are read from a `FileDescriptorSet` configured in `book.toml`:
+//!
+//! ```toml
+//! [preprocessor.proto]
+//! command = "protoc-gen-mdbook preprocess"
+//! descriptor-set = "api.pb"
+//! ```
+
+use anyhow::{anyhow, Context, Result};
+use prost::Message;
+use prost_types::FileDescriptorSet;
+use protoc_gen_mdbook::directives::Expander;
+use protoc_gen_mdbook::request_from_descriptor_set;
+use serde_json::Value;
+use std::path::Path;
+
+/// Run preprocessor with the command line `args` following the `preprocess` subcommand.
+pub fn run(args: &[String]) -> Result<()> {
+    if args.first().is_some_and(|arg| arg == "supports") {
+        // Directives expand to Markdown which is understood by all renderers.
+        return Ok(());
+    }
+
+    let input: Value = serde_json::from_reader(std::io::stdin())?;
+    let context = input
+        .get(0)
+        .ok_or_else(|| anyhow!("no preprocessor context"))?;
+    let mut book = input.get(1).cloned().ok_or_else(|| anyhow!("no book"))?;
+
+    let root = context["root"].as_str().unwrap_or(".");
+    let path = context["config"]["preprocessor"]["proto"]["descriptor-set"]
+        .as_str()
+        .ok_or_else(|| anyhow!("`preprocessor.proto.descriptor-set` not configured"))?;
+
+    let path = Path::new(root).join(path);
+    let buf = std::fs::read(&path).with_context(|| format!("reading {}", path.display()))?;
+    let request = request_from_descriptor_set(FileDescriptorSet::decode(&*buf)?, vec![]);
+    let expander = Expander::new(&request)?;
+
+    expand_items(&expander, &mut book["sections"])?;
+    serde_json::to_writer(std::io::stdout(), &book)?;
+
+    Ok(())
+}
+
+/// Expand directives in all chapters of `items` and their sub items.
+fn expand_items(expander: &Expander, items: &mut Value) -> Result<()> {
+    let Some(items) = items.as_array_mut() else {
+        return Ok(());
+    };
+
+    for item in items {
+        let Some(chapter) = item.get_mut("Chapter") else {
+            continue;
+        };
+
+        if let Some(content) = chapter["content"].as_str() {
+            let name = chapter["name"].as_str().unwrap_or_default();
+            let content = expander
+                .expand(content)
+                .with_context(|| format!("expanding chapter {name}"))?;
+            chapter["content"] = Value::String(content);
+        }
+
+        expand_items(expander, &mut chapter["sub_items"])?;
+    }
+
+    Ok(())
+}
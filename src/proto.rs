@@ -1,16 +1,45 @@
 //! Higher level wrapper types for the *Proto types from proto-types.
 
-use crate::render::filters;
-use anyhow::{anyhow, Result};
-use askama::Template;
 use prost_types::compiler::CodeGeneratorRequest;
 use prost_types::field_descriptor_proto as fdp;
 use prost_types::{
     DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
-    FileDescriptorProto, MethodDescriptorProto, ServiceDescriptorProto, SourceCodeInfo,
+    FileDescriptorProto, MethodDescriptorProto, OneofDescriptorProto, ServiceDescriptorProto,
+    SourceCodeInfo,
 };
 use std::collections::HashMap;
 
+/// Error caused by a malformed or unresolvable descriptor element.
+#[derive(Debug)]
+pub struct Error {
+    /// Name of the proto file containing the element.
+    pub file: String,
+    /// Dotted path to the element, e.g. `pkg.Service.Method`.
+    pub element: String,
+    /// Why the element could not be processed.
+    pub reason: String,
+}
+
+impl Error {
+    pub fn new(file: &str, element: &str, reason: impl Into<String>) -> Self {
+        Self {
+            file: file.to_string(),
+            element: element.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}: {}", self.file, self.element, self.reason)
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
 /// Wrap message and enum types for generic rendering.
 #[derive(PartialEq)]
 pub enum Types<'a> {
@@ -19,22 +48,22 @@ pub enum Types<'a> {
 }
 
 impl<'a> Types<'a> {
-    /// Return `true` if `name` matches.
-    pub fn has_name(&self, name: &str) -> bool {
+    /// Return the fully qualified name including the leading dot.
+    pub fn full_name(&self) -> &str {
         match self {
-            Types::Message(ty) => ty.name == name,
-            Types::Enum(ty) => ty.name == name,
+            Types::Message(ty) => &ty.full_name,
+            Types::Enum(ty) => &ty.full_name,
         }
     }
 }
 
-/// Maps from package name to all included enum and message types.
-pub type AllTypes<'a> = HashMap<String, Vec<Types<'a>>>;
+/// Symbol table mapping from fully qualified type names including the leading dot, e.g.
+/// `.pkg.Outer.Inner`, to all enum and message types at any nesting depth.
+pub type AllTypes<'a> = HashMap<String, Types<'a>>;
 
 /// A fully qualified type name including package path and leading dot.
 #[derive(PartialEq)]
 pub struct FullyQualifiedTypeName<'a> {
-    #[allow(dead_code)]
     pub original: &'a str,
     /// Package path without the leading dot
     pub package: &'a str,
@@ -42,22 +71,25 @@ pub struct FullyQualifiedTypeName<'a> {
     pub name: &'a str,
 }
 
-impl<'a> From<&'a str> for FullyQualifiedTypeName<'a> {
-    fn from(original: &'a str) -> Self {
-        let start = original.find('.').unwrap();
-        let end = original.rfind('.').unwrap();
+impl<'a> TryFrom<&'a str> for FullyQualifiedTypeName<'a> {
+    type Error = String;
 
-        Self {
+    fn try_from(original: &'a str) -> Result<Self, Self::Error> {
+        let start = original
+            .find('.')
+            .ok_or_else(|| format!("type name `{original}` is not fully qualified"))?;
+        let end = original.rfind('.').unwrap_or(start);
+
+        Ok(Self {
             original,
-            package: &original[start + 1..end],
+            // Types without package only consist of the leading dot and the name.
+            package: if start == end {
+                ""
+            } else {
+                &original[start + 1..end]
+            },
             name: &original[end + 1..],
-        }
-    }
-}
-
-impl<'a> From<&'a FieldDescriptorProto> for FullyQualifiedTypeName<'a> {
-    fn from(value: &'a FieldDescriptorProto) -> Self {
-        Self::from(value.type_name())
+        })
     }
 }
 
@@ -67,31 +99,64 @@ pub struct CustomType<'a> {
     pub name: FullyQualifiedTypeName<'a>,
 }
 
-/// Field type which is either a well-known proto type or a custom message type.
+/// Field type which is either a well-known proto type, a custom message type or a map of these.
 #[derive(PartialEq)]
 pub enum FieldType<'a> {
     WellKnown(fdp::Type),
     Custom(CustomType<'a>),
+    Map(Box<FieldType<'a>>, Box<FieldType<'a>>),
 }
 
 impl<'a> FieldType<'a> {
-    pub fn name(&self) -> &str {
+    /// Construct map type from the key and value fields of the synthetic map `entry` type.
+    fn map(entry: &'a DescriptorProto) -> Result<Self, String> {
+        let field = |number| {
+            entry
+                .field
+                .iter()
+                .find(|f| f.number() == number)
+                .ok_or_else(|| format!("map entry `{}` without field {number}", entry.name()))
+        };
+
+        let key = FieldType::try_from(field(1)?)?;
+        let value = FieldType::try_from(field(2)?)?;
+        Ok(FieldType::Map(Box::new(key), Box::new(value)))
+    }
+
+    /// Return all custom types referenced by this type.
+    pub fn custom_types(&self) -> Vec<&CustomType<'a>> {
+        match self {
+            Self::WellKnown(_) => vec![],
+            Self::Custom(ty) => vec![ty],
+            Self::Map(key, value) => {
+                let mut result = key.custom_types();
+                result.append(&mut value.custom_types());
+                result
+            }
+        }
+    }
+}
+
+impl<'a> std::fmt::Display for FieldType<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::WellKnown(ty) => scalar_type_name(*ty),
-            Self::Custom(ty) => ty.name.name,
+            Self::WellKnown(ty) => write!(f, "{}", scalar_type_name(*ty)),
+            Self::Custom(ty) => write!(f, "{}", ty.name.name),
+            Self::Map(key, value) => write!(f, "map<{key}, {value}>"),
         }
     }
 }
 
-impl<'a> From<&'a FieldDescriptorProto> for FieldType<'a> {
-    fn from(field: &'a FieldDescriptorProto) -> Self {
+impl<'a> TryFrom<&'a FieldDescriptorProto> for FieldType<'a> {
+    type Error = String;
+
+    fn try_from(field: &'a FieldDescriptorProto) -> Result<Self, Self::Error> {
         if field.type_name.is_some() {
-            // unsafe: we do not yet guarantee that the field contains a leading dot.
-            FieldType::Custom(CustomType {
-                name: FullyQualifiedTypeName::from(field),
-            })
+            Ok(FieldType::Custom(CustomType {
+                name: FullyQualifiedTypeName::try_from(field.type_name())?,
+            }))
         } else {
-            FieldType::WellKnown(field.r#type())
+            Ok(FieldType::WellKnown(field.r#type()))
         }
     }
 }
@@ -104,18 +169,40 @@ pub struct Field<'a> {
     pub number: i32,
     pub optional: bool,
     pub repeated: bool,
+    /// Index of the `oneof` declaration containing this field, `None` for proto3 `optional`.
+    pub oneof_index: Option<i32>,
+    pub leading_comments: &'a str,
+    pub trailing_comments: &'a str,
+}
+
+/// `oneof` declaration of a message type.
+#[derive(PartialEq)]
+pub struct Oneof<'a> {
+    pub name: &'a str,
+    /// Index of the declaration as referenced by [`Field::oneof_index`].
+    pub index: i32,
     pub leading_comments: &'a str,
     pub trailing_comments: &'a str,
 }
 
+/// Member of a message type in order of field numbers, either a single field or all fields of a
+/// `oneof` declaration.
+pub enum Member<'a, 'b> {
+    Field(&'b Field<'a>),
+    Oneof(&'b Oneof<'a>, Vec<&'b Field<'a>>),
+}
+
 /// Message types referenced as inputs and outputs in methods.
-#[derive(PartialEq, Template)]
-#[template(path = "message_type.md", escape = "none")]
+#[derive(PartialEq)]
 pub struct MessageType<'a> {
     pub name: &'a str,
+    pub full_name: String,
     pub description: &'a str,
     pub fields: Vec<Field<'a>>,
+    /// All `oneof` declarations except synthetic ones of proto3 `optional` fields.
+    pub oneofs: Vec<Oneof<'a>>,
     pub nested: Vec<MessageType<'a>>,
+    pub enums: Vec<EnumType<'a>>,
     pub depth: usize,
 }
 
@@ -132,6 +219,7 @@ pub struct EnumValue<'a> {
 #[derive(PartialEq)]
 pub struct EnumType<'a> {
     pub name: &'a str,
+    pub full_name: String,
     pub description: &'a str,
     pub values: Vec<EnumValue<'a>>,
 }
@@ -157,6 +245,7 @@ pub struct Method<'a> {
 /// gRPC service type.
 pub struct Service<'a> {
     pub name: &'a str,
+    pub file: &'a str,
     pub package: &'a str,
     pub description: &'a str,
     pub deprecated: bool,
@@ -187,40 +276,68 @@ fn scalar_type_name(ty: fdp::Type) -> &'static str {
     }
 }
 
-/// Return all message types for all compiled protos mapped from their package tree.
-pub fn get_types(request: &CodeGeneratorRequest) -> AllTypes {
-    let mut result: HashMap<String, Vec<Types>> = HashMap::new();
+/// Return all message and enum types for all compiled protos, including nested ones, mapped from
+/// their fully qualified name.
+pub fn get_types(request: &CodeGeneratorRequest) -> Result<AllTypes<'_>> {
+    let mut result = AllTypes::new();
 
     for proto in &request.proto_file {
-        let package = proto.package();
-        let info = proto.source_code_info.as_ref().unwrap();
+        let scope = package_scope(proto.package());
+        let info = source_code_info(proto);
 
-        let mut message_types = proto
-            .message_type
-            .iter()
-            .enumerate()
-            .map(|(idx, ty)| Types::Message(MessageType::from(ty, as_i32(idx), info, 0)))
-            .collect::<Vec<Types>>();
+        for (idx, ty) in proto.message_type.iter().enumerate() {
+            register_message(&mut result, proto, ty, &scope, &[4, as_i32(idx)], info)?;
+        }
 
-        result
-            .entry(package.to_string())
-            .or_default()
-            .append(&mut message_types);
+        for (idx, ty) in proto.enum_type.iter().enumerate() {
+            let ty = EnumType::from(ty, &scope, &[5, as_i32(idx)], info);
+            result.insert(ty.full_name.clone(), Types::Enum(ty));
+        }
+    }
 
-        let mut enum_types = proto
-            .enum_type
-            .iter()
-            .enumerate()
-            .map(|(idx, ty)| Types::Enum(EnumType::from(ty, as_i32(idx), info)))
-            .collect::<Vec<Types>>();
+    Ok(result)
+}
 
-        result
-            .entry(package.to_string())
-            .or_default()
-            .append(&mut enum_types);
+/// Register `message_type` found at `path` in `scope` and all its nested message and enum types.
+fn register_message<'a>(
+    types: &mut AllTypes<'a>,
+    proto: &'a FileDescriptorProto,
+    message_type: &'a DescriptorProto,
+    scope: &str,
+    path: &[i32],
+    info: &'a SourceCodeInfo,
+) -> Result<()> {
+    let ty = MessageType::from(proto, message_type, scope, path, info, 0)?;
+
+    for (idx, nested) in message_type.nested_type.iter().enumerate() {
+        if is_map_entry(nested) {
+            continue;
+        }
+
+        let path = [path, &[3, as_i32(idx)]].concat();
+        register_message(types, proto, nested, &ty.full_name, &path, info)?;
+    }
+
+    for (idx, nested) in message_type.enum_type.iter().enumerate() {
+        let path = [path, &[4, as_i32(idx)]].concat();
+        let nested = EnumType::from(nested, &ty.full_name, &path, info);
+        types.insert(nested.full_name.clone(), Types::Enum(nested));
     }
 
-    result
+    types.insert(ty.full_name.clone(), Types::Message(ty));
+    Ok(())
+}
+
+/// Find file descriptor `name` in `request`.
+pub fn get_file<'a>(
+    request: &'a CodeGeneratorRequest,
+    name: &str,
+) -> Result<&'a FileDescriptorProto> {
+    request
+        .proto_file
+        .iter()
+        .find(|p| p.name() == name)
+        .ok_or_else(|| Error::new(name, name, "file not found in request"))
 }
 
 /// Construct all `Service`s of file descriptor `name` in `request`.
@@ -229,25 +346,99 @@ pub fn get_services<'a>(
     name: &str,
     types: &'a AllTypes,
 ) -> Result<Vec<Service<'a>>> {
-    let proto = request
-        .proto_file
-        .iter()
-        .find(|p| p.name() == name)
-        .ok_or_else(|| anyhow!("{name} not found"))?;
+    let proto = get_file(request, name)?;
 
-    let info = proto
-        .source_code_info
-        .as_ref()
-        .ok_or_else(|| anyhow!("no source code info"))?;
+    let info = source_code_info(proto);
 
-    let services = proto
+    proto
         .service
         .iter()
         .enumerate()
         .map(|(idx, service)| Service::from(proto, service, types, as_i32(idx), info))
-        .collect::<Vec<_>>();
+        .collect()
+}
+
+/// Return all top-level message and enum types declared in file descriptor `name` in `request`.
+pub fn get_file_types<'a>(
+    request: &'a CodeGeneratorRequest,
+    name: &str,
+    types: &'a AllTypes,
+) -> Result<Vec<&'a Types<'a>>> {
+    let proto = get_file(request, name)?;
+
+    let scope = package_scope(proto.package());
+
+    proto
+        .message_type
+        .iter()
+        .map(DescriptorProto::name)
+        .chain(proto.enum_type.iter().map(EnumDescriptorProto::name))
+        .map(|name| {
+            let full_name = format!("{scope}.{name}");
+
+            types
+                .get(&full_name)
+                .ok_or_else(|| Error::new(proto.name(), &full_name[1..], "type not registered"))
+        })
+        .collect()
+}
+
+/// Source code info of descriptors compiled without it, which have no descriptions.
+static NO_SOURCE_CODE_INFO: SourceCodeInfo = SourceCodeInfo {
+    location: Vec::new(),
+};
+
+/// Get source code info of `proto` which is required for descriptions.
+fn source_code_info(proto: &FileDescriptorProto) -> &SourceCodeInfo {
+    proto
+        .source_code_info
+        .as_ref()
+        .unwrap_or(&NO_SOURCE_CODE_INFO)
+}
+
+/// Return `true` if `message_type` is the synthetic entry type of a map field.
+fn is_map_entry(message_type: &DescriptorProto) -> bool {
+    message_type
+        .options
+        .as_ref()
+        .is_some_and(|opt| opt.map_entry())
+}
+
+/// Return the synthetic map entry type nested in `message_type` named `full_name` if `field` is a
+/// map.
+fn map_entry<'a>(
+    message_type: &'a DescriptorProto,
+    full_name: &str,
+    field: &FieldDescriptorProto,
+) -> Option<&'a DescriptorProto> {
+    let name = field
+        .type_name()
+        .strip_prefix(full_name)?
+        .strip_prefix('.')?;
+
+    message_type
+        .nested_type
+        .iter()
+        .find(|d| d.name() == name && is_map_entry(d))
+}
 
-    Ok(services)
+/// Join `package` and `name` with a dot unless `package` is empty.
+pub fn qualified_name(package: &str, name: &str) -> String {
+    if package.is_empty() {
+        name.to_string()
+    } else {
+        format!("{package}.{name}")
+    }
+}
+
+/// Return scope of top-level types in `package`, i.e. the package with a leading dot or nothing
+/// if there is no package.
+fn package_scope(package: &str) -> String {
+    if package.is_empty() {
+        String::new()
+    } else {
+        format!(".{package}")
+    }
 }
 
 /// Get leading comments for the given `path` or empty string if not found matching.
@@ -287,60 +478,164 @@ impl std::fmt::Display for CallType {
 }
 
 impl<'a> Field<'a> {
-    /// Construct field.
-    fn from(field: &'a FieldDescriptorProto, info: &'a SourceCodeInfo, path: &[i32]) -> Self {
-        let ty = FieldType::from(field);
+    /// Construct field, returning the reason if the field type is malformed. `entry` is the
+    /// synthetic map entry type if the field is a map.
+    fn from(
+        field: &'a FieldDescriptorProto,
+        entry: Option<&'a DescriptorProto>,
+        info: &'a SourceCodeInfo,
+        path: &[i32],
+    ) -> Result<Self, String> {
+        let ty = match entry {
+            Some(entry) => FieldType::map(entry)?,
+            None => FieldType::try_from(field)?,
+        };
+
         let location = info.location.iter().find(|l| l.path == *path);
         let leading_comments = location.map_or("", |l| l.leading_comments());
-        let trailing_comments = location.map_or("", |l| l.trailing_comments());
-        let repeated = field
-            .label
-            .map_or(false, |l| l == fdp::Label::Repeated.into());
+        let trailing_comments = location.map_or("", |l| l.trailing_comments().trim_end());
+        let repeated = entry.is_none() && field.label == Some(fdp::Label::Repeated.into());
+        let optional = field.proto3_optional();
 
-        Self {
+        Ok(Self {
             name: field.name(),
             ty,
             number: field.number(),
-            optional: field.proto3_optional(),
+            optional,
             repeated,
+            oneof_index: field.oneof_index.filter(|_| !optional),
             leading_comments,
             trailing_comments,
-        }
+        })
     }
 }
 
 impl<'a> MessageType<'a> {
-    /// Construct message type.
+    /// Construct message type declared in `scope` and found at `path`.
     fn from(
+        proto: &'a FileDescriptorProto,
         message_type: &'a DescriptorProto,
-        idx: i32,
+        scope: &str,
+        path: &[i32],
         info: &'a SourceCodeInfo,
         depth: usize,
-    ) -> Self {
-        let description = get_description(info, &[4, idx]);
+    ) -> Result<Self> {
+        let description = get_description(info, path);
+        let full_name = format!("{scope}.{}", message_type.name());
 
         let mut fields = message_type
             .field
             .iter()
             .enumerate()
-            .map(|(i, f)| Field::from(f, info, &[4, idx, 2, as_i32(i)]))
-            .collect::<Vec<_>>();
+            .map(|(i, f)| {
+                let entry = map_entry(message_type, &full_name, f);
+
+                Field::from(f, entry, info, &[path, &[2, as_i32(i)]].concat()).map_err(|reason| {
+                    let element = format!("{}.{}", &full_name[1..], f.name());
+                    Error::new(proto.name(), &element, reason)
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-        fields.sort_by(|a, b| a.number.cmp(&b.number));
+        fields.sort_by_key(|f| f.number);
+
+        let oneofs = message_type
+            .oneof_decl
+            .iter()
+            .enumerate()
+            .map(|(i, oneof)| {
+                Oneof::from(oneof, as_i32(i), info, &[path, &[8, as_i32(i)]].concat())
+            })
+            .filter(|oneof| fields.iter().any(|f| f.oneof_index == Some(oneof.index)))
+            .collect();
 
         let nested = message_type
             .nested_type
             .iter()
-            .map(|d| MessageType::from(d, idx, info, depth + 1))
+            .enumerate()
+            .filter(|(_, d)| !is_map_entry(d))
+            .map(|(i, d)| {
+                let path = [path, &[3, as_i32(i)]].concat();
+                MessageType::from(proto, d, &full_name, &path, info, depth + 1)
+            })
+            .collect::<Result<_>>()?;
+
+        let enums = message_type
+            .enum_type
+            .iter()
+            .enumerate()
+            .map(|(i, e)| EnumType::from(e, &full_name, &[path, &[4, as_i32(i)]].concat(), info))
             .collect();
 
-        Self {
+        Ok(Self {
             name: message_type.name(),
+            full_name,
             description,
             fields,
+            oneofs,
             nested,
+            enums,
             depth,
+        })
+    }
+}
+
+impl<'a> Oneof<'a> {
+    /// Construct `oneof` declaration with `index`.
+    fn from(
+        oneof: &'a OneofDescriptorProto,
+        index: i32,
+        info: &'a SourceCodeInfo,
+        path: &[i32],
+    ) -> Self {
+        let location = info.location.iter().find(|l| l.path == *path);
+
+        Self {
+            name: oneof.name(),
+            index,
+            leading_comments: location.map_or("", |l| l.leading_comments()),
+            trailing_comments: location.map_or("", |l| l.trailing_comments().trim_end()),
+        }
+    }
+}
+
+impl<'a> MessageType<'a> {
+    /// Return fields and `oneof` declarations ordered by their (lowest) field number.
+    pub fn members(&self) -> Vec<Member<'a, '_>> {
+        let mut result = self
+            .fields
+            .iter()
+            .filter(|f| f.oneof_index.is_none())
+            .map(Member::Field)
+            .collect::<Vec<_>>();
+
+        for oneof in &self.oneofs {
+            let fields = self
+                .fields
+                .iter()
+                .filter(|f| f.oneof_index == Some(oneof.index))
+                .collect();
+
+            result.push(Member::Oneof(oneof, fields));
+        }
+
+        result.sort_by_key(|m| match m {
+            Member::Field(field) => field.number,
+            Member::Oneof(_, fields) => fields.first().map_or(0, |f| f.number),
+        });
+
+        result
+    }
+
+    /// Return fields of this and all nested message types along with their declaring type.
+    pub fn all_fields(&self) -> Vec<(&MessageType<'a>, &Field<'a>)> {
+        let mut result = self.fields.iter().map(|f| (self, f)).collect::<Vec<_>>();
+
+        for nested in &self.nested {
+            result.append(&mut nested.all_fields());
         }
+
+        result
     }
 }
 
@@ -361,21 +656,27 @@ impl<'a> EnumValue<'a> {
 }
 
 impl<'a> EnumType<'a> {
-    /// Construct enum type.
-    fn from(enum_type: &'a EnumDescriptorProto, idx: i32, info: &'a SourceCodeInfo) -> Self {
-        let description = get_description(info, &[5, idx]);
+    /// Construct enum type declared in `scope` and found at `path`.
+    fn from(
+        enum_type: &'a EnumDescriptorProto,
+        scope: &str,
+        path: &[i32],
+        info: &'a SourceCodeInfo,
+    ) -> Self {
+        let description = get_description(info, path);
 
         let mut values = enum_type
             .value
             .iter()
             .enumerate()
-            .map(|(i, v)| EnumValue::from(v, info, &[5, idx, 2, as_i32(i)]))
+            .map(|(i, v)| EnumValue::from(v, info, &[path, &[2, as_i32(i)]].concat()))
             .collect::<Vec<_>>();
 
-        values.sort_by(|a, b| a.number.cmp(&b.number));
+        values.sort_by_key(|v| v.number);
 
         Self {
             name: enum_type.name(),
+            full_name: format!("{scope}.{}", enum_type.name()),
             description,
             values,
         }
@@ -389,17 +690,19 @@ impl<'a> Method<'a> {
         path: &mut Vec<i32>,
         idx: i32,
         info: &'a SourceCodeInfo,
-    ) -> Self {
+    ) -> Result<Self, String> {
         path.push(idx);
         let description = get_description(info, path);
         path.pop();
 
-        let name = FullyQualifiedTypeName::from(method.input_type());
-        let types = types.get(name.package).unwrap();
-        let input_type = types.iter().find(|ty| ty.has_name(name.name)).unwrap();
+        let resolve = |name: &str| {
+            types
+                .get(name)
+                .ok_or_else(|| format!("unresolved type `{name}`"))
+        };
 
-        let name = FullyQualifiedTypeName::from(method.output_type());
-        let output_type = types.iter().find(|ty| ty.has_name(name.name)).unwrap();
+        let input_type = resolve(method.input_type())?;
+        let output_type = resolve(method.output_type())?;
 
         let deprecated = method
             .options
@@ -407,14 +710,14 @@ impl<'a> Method<'a> {
             .and_then(|opt| opt.deprecated)
             .unwrap_or(false);
 
-        Self {
+        Ok(Self {
             name: method.name(),
             call_type: method.into(),
             description,
             deprecated,
             input_type,
             output_type,
-        }
+        })
     }
 }
 
@@ -425,7 +728,7 @@ impl<'a> Service<'a> {
         types: &'a AllTypes,
         idx: i32,
         info: &'a SourceCodeInfo,
-    ) -> Self {
+    ) -> Result<Self> {
         let mut path = vec![6, idx];
 
         let deprecated = service
@@ -440,29 +743,369 @@ impl<'a> Service<'a> {
             .method
             .iter()
             .enumerate()
-            .map(|(idx, method)| Method::from(method, types, &mut path, as_i32(idx), info))
-            .collect::<Vec<_>>();
+            .map(|(idx, method)| {
+                Method::from(method, types, &mut path, as_i32(idx), info).map_err(|reason| {
+                    let element = qualified_name(proto.package(), service.name());
+                    let element = format!("{element}.{}", method.name());
+                    Error::new(proto.name(), &element, reason)
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
 
         path.pop();
 
-        Self {
+        Ok(Self {
             name: service.name(),
+            file: proto.name(),
             package: proto.package(),
             description: get_description(info, &path),
             deprecated,
             methods,
-        }
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::FullyQualifiedTypeName;
+    use super::{get_services, get_types, FullyQualifiedTypeName, Member, Types};
+    use prost_types::compiler::CodeGeneratorRequest;
+    use prost_types::field_descriptor_proto::{Label, Type};
+    use prost_types::source_code_info::Location;
+    use prost_types::{
+        DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+        FileDescriptorProto, MessageOptions, MethodDescriptorProto, OneofDescriptorProto,
+        ServiceDescriptorProto, SourceCodeInfo,
+    };
+
+    fn message(name: &str, nested_type: Vec<DescriptorProto>) -> DescriptorProto {
+        DescriptorProto {
+            name: Some(name.to_string()),
+            nested_type,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn register_nested_types() {
+        let mut outer = message(
+            "Outer",
+            vec![message("Inner", vec![message("Deep", vec![])])],
+        );
+
+        outer.enum_type.push(EnumDescriptorProto {
+            name: Some("Kind".to_string()),
+            ..Default::default()
+        });
+
+        let request = CodeGeneratorRequest {
+            proto_file: vec![
+                FileDescriptorProto {
+                    package: Some("foo".to_string()),
+                    message_type: vec![outer, message("Inner", vec![])],
+                    source_code_info: Some(SourceCodeInfo::default()),
+                    ..Default::default()
+                },
+                FileDescriptorProto {
+                    package: Some("bar".to_string()),
+                    message_type: vec![message("Inner", vec![])],
+                    source_code_info: Some(SourceCodeInfo::default()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let types = get_types(&request).unwrap();
+        assert_eq!(types.len(), 6);
+        assert!(matches!(types[".foo.Outer.Inner.Deep"], Types::Message(_)));
+        assert!(matches!(types[".foo.Outer.Kind"], Types::Enum(_)));
+        assert!(types.contains_key(".foo.Outer.Inner"));
+        assert!(types.contains_key(".foo.Inner"));
+        assert!(types.contains_key(".bar.Inner"));
+    }
 
     #[test]
     fn fully_qualified_type_name_processing() {
-        let name = FullyQualifiedTypeName::from(".foo.bar.Baz");
+        let name = FullyQualifiedTypeName::try_from(".foo.bar.Baz").unwrap();
         assert_eq!(name.package, "foo.bar");
         assert_eq!(name.name, "Baz");
+        assert!(FullyQualifiedTypeName::try_from("Baz").is_err());
+
+        let name = FullyQualifiedTypeName::try_from(".Baz").unwrap();
+        assert_eq!(name.package, "");
+        assert_eq!(name.name, "Baz");
+    }
+
+    #[test]
+    fn register_types_without_package() {
+        let request = CodeGeneratorRequest {
+            proto_file: vec![FileDescriptorProto {
+                message_type: vec![message("Outer", vec![message("Inner", vec![])])],
+                source_code_info: Some(SourceCodeInfo::default()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let types = get_types(&request).unwrap();
+        assert!(types.contains_key(".Outer"));
+        assert!(types.contains_key(".Outer.Inner"));
+    }
+
+    #[test]
+    fn report_unresolved_method_type() {
+        let request = CodeGeneratorRequest {
+            proto_file: vec![FileDescriptorProto {
+                name: Some("foo.proto".to_string()),
+                package: Some("foo".to_string()),
+                message_type: vec![message("Request", vec![])],
+                service: vec![ServiceDescriptorProto {
+                    name: Some("Service".to_string()),
+                    method: vec![MethodDescriptorProto {
+                        name: Some("Get".to_string()),
+                        input_type: Some(".foo.Request".to_string()),
+                        output_type: Some(".foo.Missing".to_string()),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                source_code_info: Some(SourceCodeInfo::default()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let types = get_types(&request).unwrap();
+        let err = get_services(&request, "foo.proto", &types).err().unwrap();
+        assert_eq!(err.file, "foo.proto");
+        assert_eq!(err.element, "foo.Service.Get");
+        assert_eq!(err.reason, "unresolved type `.foo.Missing`");
+    }
+
+    #[test]
+    fn resolve_method_types_across_packages() {
+        let request = CodeGeneratorRequest {
+            proto_file: vec![
+                FileDescriptorProto {
+                    name: Some("b.proto".to_string()),
+                    package: Some("b".to_string()),
+                    message_type: vec![message("Message", vec![])],
+                    source_code_info: Some(SourceCodeInfo::default()),
+                    ..Default::default()
+                },
+                FileDescriptorProto {
+                    name: Some("a.proto".to_string()),
+                    package: Some("a".to_string()),
+                    dependency: vec!["b.proto".to_string()],
+                    message_type: vec![message("Message", vec![])],
+                    service: vec![ServiceDescriptorProto {
+                        name: Some("Service".to_string()),
+                        method: vec![MethodDescriptorProto {
+                            name: Some("Get".to_string()),
+                            input_type: Some(".a.Message".to_string()),
+                            output_type: Some(".b.Message".to_string()),
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    }],
+                    source_code_info: Some(SourceCodeInfo::default()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let types = get_types(&request).unwrap();
+        let services = get_services(&request, "a.proto", &types).unwrap();
+        let method = &services[0].methods[0];
+        assert_eq!(method.input_type.full_name(), ".a.Message");
+        assert_eq!(method.output_type.full_name(), ".b.Message");
+    }
+
+    #[test]
+    fn describe_nested_types() {
+        let location = |path: &[i32], comment: &str| Location {
+            path: path.to_vec(),
+            leading_comments: Some(comment.to_string()),
+            trailing_comments: Some(format!("{comment} trailing")),
+            ..Default::default()
+        };
+
+        let field = |name: &str| FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(1),
+            ..Default::default()
+        };
+
+        let mut inner = message("Inner", vec![]);
+        inner.field.push(field("inner_field"));
+        inner.enum_type.push(EnumDescriptorProto {
+            name: Some("Kind".to_string()),
+            value: vec![EnumValueDescriptorProto {
+                name: Some("KIND_UNSPECIFIED".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+
+        let mut outer = message("Outer", vec![message("Empty", vec![]), inner]);
+        outer.field.push(field("outer_field"));
+
+        let request = CodeGeneratorRequest {
+            proto_file: vec![FileDescriptorProto {
+                package: Some("foo".to_string()),
+                message_type: vec![outer],
+                source_code_info: Some(SourceCodeInfo {
+                    location: vec![
+                        location(&[4, 0], "outer"),
+                        location(&[4, 0, 2, 0], "outer field"),
+                        location(&[4, 0, 3, 1], "inner"),
+                        location(&[4, 0, 3, 1, 2, 0], "inner field"),
+                        location(&[4, 0, 3, 1, 4, 0], "kind"),
+                        location(&[4, 0, 3, 1, 4, 0, 2, 0], "kind value"),
+                    ],
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let types = get_types(&request).unwrap();
+
+        let Types::Message(outer) = &types[".foo.Outer"] else {
+            panic!("not a message");
+        };
+
+        assert_eq!(outer.description, "outer");
+        assert_eq!(outer.fields[0].leading_comments, "outer field");
+        assert_eq!(outer.nested[0].description, "");
+        assert_eq!(outer.nested[1].description, "inner");
+        assert_eq!(outer.nested[1].fields[0].leading_comments, "inner field");
+        assert_eq!(
+            outer.nested[1].fields[0].trailing_comments,
+            "inner field trailing"
+        );
+        assert_eq!(outer.nested[1].enums[0].description, "kind");
+
+        let Types::Enum(kind) = &types[".foo.Outer.Inner.Kind"] else {
+            panic!("not an enum");
+        };
+
+        assert_eq!(kind.description, "kind");
+        assert_eq!(kind.values[0].leading_comments, "kind value");
+        assert_eq!(kind.values[0].trailing_comments, "kind value trailing");
+    }
+
+    #[test]
+    fn render_map_fields() {
+        let field = |name: &str, number, ty: Type, type_name: Option<&str>| FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(Label::Repeated.into()),
+            r#type: Some(ty.into()),
+            type_name: type_name.map(ToString::to_string),
+            ..Default::default()
+        };
+
+        let mut entry = message("FoosEntry", vec![]);
+        entry.field = vec![
+            field("key", 1, Type::String, None),
+            field("value", 2, Type::Message, Some(".foo.Foo")),
+        ];
+        entry.options = Some(MessageOptions {
+            map_entry: Some(true),
+            ..Default::default()
+        });
+
+        let mut map = message("Map", vec![entry]);
+        map.field = vec![field("foos", 1, Type::Message, Some(".foo.Map.FoosEntry"))];
+
+        let request = CodeGeneratorRequest {
+            proto_file: vec![FileDescriptorProto {
+                package: Some("foo".to_string()),
+                message_type: vec![map, message("Foo", vec![])],
+                source_code_info: Some(SourceCodeInfo::default()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let types = get_types(&request).unwrap();
+        assert!(!types.contains_key(".foo.Map.FoosEntry"));
+
+        let Types::Message(map) = &types[".foo.Map"] else {
+            panic!("not a message");
+        };
+
+        assert!(map.nested.is_empty());
+        assert!(!map.fields[0].repeated);
+        assert_eq!(map.fields[0].ty.to_string(), "map<string, Foo>");
+        assert_eq!(map.fields[0].ty.custom_types()[0].name.original, ".foo.Foo");
+    }
+
+    #[test]
+    fn group_oneof_fields() {
+        let field = |name: &str, number, oneof_index, proto3_optional| FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            r#type: Some(Type::Int32.into()),
+            oneof_index,
+            proto3_optional,
+            ..Default::default()
+        };
+
+        let oneof = |name: &str| OneofDescriptorProto {
+            name: Some(name.to_string()),
+            ..Default::default()
+        };
+
+        let mut choice = message("Choice", vec![]);
+        choice.field = vec![
+            field("plain", 1, None, None),
+            field("first", 3, Some(0), None),
+            field("second", 2, Some(0), None),
+            field("opt", 4, Some(1), Some(true)),
+        ];
+        choice.oneof_decl = vec![oneof("choice"), oneof("_opt")];
+
+        let request = CodeGeneratorRequest {
+            proto_file: vec![FileDescriptorProto {
+                package: Some("foo".to_string()),
+                message_type: vec![choice],
+                source_code_info: Some(SourceCodeInfo {
+                    location: vec![Location {
+                        path: vec![4, 0, 8, 0],
+                        leading_comments: Some("choice".to_string()),
+                        ..Default::default()
+                    }],
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let types = get_types(&request).unwrap();
+
+        let Types::Message(choice) = &types[".foo.Choice"] else {
+            panic!("not a message");
+        };
+
+        assert_eq!(choice.oneofs.len(), 1);
+        assert_eq!(choice.oneofs[0].leading_comments, "choice");
+
+        let members = choice.members();
+        assert_eq!(members.len(), 3);
+        assert!(matches!(members[0], Member::Field(f) if f.name == "plain"));
+        assert!(matches!(members[2], Member::Field(f) if f.name == "opt" && f.optional));
+
+        let Member::Oneof(oneof, fields) = &members[1] else {
+            panic!("not a oneof");
+        };
+
+        assert_eq!(oneof.name, "choice");
+        assert_eq!(
+            fields.iter().map(|f| f.name).collect::<Vec<_>>(),
+            ["second", "first"]
+        );
     }
 }
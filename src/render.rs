@@ -1,108 +1,897 @@
-use crate::{proto, Options};
+use crate::{model, proto, Options, Style, TypesSection};
 use askama::Template;
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+pub mod asciidoc;
+pub mod html;
+pub mod links;
+pub mod mdx;
+pub mod rst;
+
+use links::{Links, User};
+
+/// Type listed as input or output of a method.
+struct Related<'a> {
+    ty: &'a proto::Types<'a>,
+    /// Fields of `ty` whose type is listed above and encloses them, closing a cycle.
+    recursive: Vec<&'a proto::Field<'a>>,
+}
+
+/// Markup of declarations specific to an output format, plain text as in code blocks by default.
+trait Syntax {
+    /// Return keyword `word`.
+    fn keyword(&self, word: &str) -> String {
+        word.to_string()
+    }
+
+    /// Return `comment` spanning one or more lines starting with `//`.
+    fn comment(&self, comment: &str) -> String {
+        comment.to_string()
+    }
+
+    /// Return field or enum value `number`.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn number(&self, number: &i32) -> String {
+        number.to_string()
+    }
+
+    /// Return `name` of the declared type, carrying anchor `id` if set.
+    fn type_name(&self, name: &str, _id: Option<&str>) -> String {
+        name.to_string()
+    }
+
+    /// Return type `ty` of a field.
+    fn field_type(&self, ty: &proto::FieldType) -> String {
+        ty.to_string()
+    }
+}
+
+impl<T: Syntax + ?Sized> Syntax for &T {
+    fn keyword(&self, word: &str) -> String {
+        (**self).keyword(word)
+    }
+
+    fn comment(&self, comment: &str) -> String {
+        (**self).comment(comment)
+    }
+
+    fn number(&self, number: &i32) -> String {
+        (**self).number(number)
+    }
+
+    fn type_name(&self, name: &str, id: Option<&str>) -> String {
+        (**self).type_name(name, id)
+    }
+
+    fn field_type(&self, ty: &proto::FieldType) -> String {
+        (**self).field_type(ty)
+    }
+}
+
+/// Declarations in Markdown code blocks which cannot carry any markup.
+struct Code;
+
+impl Syntax for Code {}
+
+/// Enum type declared on its own or nested in a message type.
+#[derive(Template)]
+#[template(path = "enum_type.proto", escape = "none")]
+struct Enum<'a, 'b> {
+    ty: &'b proto::EnumType<'a>,
+    depth: usize,
+    syntax: &'b dyn Syntax,
+    /// Types whose declaration already carries the anchor, no anchors are placed if not set.
+    anchors: Option<&'b Anchors>,
+}
+
+/// Message type declared as part of a [`Related`] type.
+#[derive(Template)]
+#[template(path = "message_type.proto", escape = "none")]
+struct Message<'a, 'b> {
+    ty: &'b proto::MessageType<'a>,
+    recursive: &'b [&'a proto::Field<'a>],
+    syntax: &'b dyn Syntax,
+    /// Types whose declaration already carries the anchor, no anchors are placed if not set.
+    anchors: Option<&'b Anchors>,
+}
+
+/// Message type rendered as table of its fields followed by those of its nested types.
+#[derive(Template)]
+#[template(path = "message_table.md", escape = "none")]
+struct MessageTable<'a, 'b> {
+    ty: &'b proto::MessageType<'a>,
+    page: &'b Page<'a>,
+    /// Types whose table already carries the anchor.
+    anchors: &'b Anchors,
+}
+
+/// Enum type rendered as table of its values.
+#[derive(Template)]
+#[template(path = "enum_table.md", escape = "none")]
+struct EnumTable<'a, 'b> {
+    ty: &'b proto::EnumType<'a>,
+    /// Types whose table already carries the anchor.
+    anchors: &'b Anchors,
+}
 
 struct Method<'a> {
     name: &'a str,
+    /// Anchor of the method, its package-qualified name.
+    id: String,
     call_type: proto::CallType,
     description: &'a str,
     deprecated: bool,
-    input_types: Vec<&'a proto::Types<'a>>,
-    output_types: Vec<&'a proto::Types<'a>>,
+    /// Package-qualified name of the input type.
+    input_name: &'a str,
+    /// Package-qualified name of the output type.
+    output_name: &'a str,
+    input_types: Vec<Related<'a>>,
+    output_types: Vec<Related<'a>>,
 }
 
 struct Service<'a> {
     name: &'a str,
     package: &'a str,
+    /// Package-qualified name or just the name if there is no package.
+    full_name: String,
     description: &'a str,
     deprecated: bool,
     methods: Vec<Method<'a>>,
     deprecated_methods: Vec<Method<'a>>,
 }
 
+/// Page of a proto file listed in the summary.
+pub struct Chapter<'a> {
+    pub file: &'a str,
+    pub package: &'a str,
+    /// Name of the generated page.
+    pub page: String,
+    /// Package-qualified names of the services with the names of their methods.
+    pub services: Vec<(String, Vec<&'a str>)>,
+}
+
+/// Entry in the summary linking to a page or a heading on a page.
+struct SummaryItem {
+    depth: usize,
+    title: String,
+    link: String,
+}
+
+/// Table of contents of an mdBook.
+#[derive(Template)]
+#[template(path = "SUMMARY.md", escape = "none")]
+pub struct Summary {
+    items: Vec<SummaryItem>,
+}
+
+/// Minimal `book.toml` for a book generated into its source directory.
+#[derive(Template)]
+#[template(path = "book.toml", escape = "none")]
+pub struct Book<'a> {
+    pub title: &'a str,
+}
+
+/// Front matter for static site generators prepended to a page.
+#[derive(Template)]
+#[template(path = "front_matter.md", escape = "none")]
+pub struct FrontMatter<'a> {
+    /// Use TOML instead of YAML.
+    pub toml: bool,
+    pub title: String,
+    /// Position of the page in the order of the proto files.
+    pub weight: usize,
+    pub package: &'a str,
+    pub deprecated: bool,
+    /// Package-qualified names of all services.
+    pub services: Vec<String>,
+}
+
+/// highlight.js language definition for protocol buffers referenced by `book.toml`.
+pub const HIGHLIGHT_JS: &str = include_str!("../templates/protobuf.js");
+
 #[derive(Template)]
 #[template(path = "template.md")]
 pub struct Page<'a> {
     services: Vec<Service<'a>>,
+    /// Types documented on their own regardless of their use in services.
+    types: Vec<Related<'a>>,
     options: &'a Options,
+    /// Types whose declaration already carries the anchor.
+    anchors: Anchors,
+    /// Resolver of links to other pages, only linking within this page if not set.
+    links: Option<&'a Links>,
+    /// Name of this page as known to `links`.
+    name: &'a str,
+}
+
+/// Names of types whose declaration already carries the anchor linked to by field types.
+type Anchors = RefCell<HashSet<String>>;
+
+/// Return package-qualified `name` if this is the first declaration of the type with fully
+/// qualified `name`, which then carries the anchor.
+fn first_anchor<'n>(anchors: &Anchors, name: &'n str) -> Option<&'n str> {
+    let name = &name[1..];
+    anchors
+        .borrow_mut()
+        .insert(name.to_string())
+        .then_some(name)
 }
 
-/// Descend field message types starting from `ty` recursively and return them.
-#[must_use]
-fn gather_types<'a>(ty: &'a proto::Types, types: &'a proto::AllTypes) -> Vec<&'a proto::Types<'a>> {
+/// Return those of the package-qualified `names` which are declared for the first time and then
+/// carry the anchor.
+fn first_anchors<'n>(anchors: &Anchors, names: Vec<&'n str>) -> Vec<&'n str> {
+    let mut anchors = anchors.borrow_mut();
+    names
+        .into_iter()
+        .filter(|name| anchors.insert((*name).to_string()))
+        .collect()
+}
+
+/// Return `true` if `name` is `scope` or nested in `scope`.
+fn is_in_scope(name: &str, scope: &str) -> bool {
+    name.strip_prefix(scope)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// Return `types` each listed once, without those nested in another listed type.
+///
+/// Fields are not marked as recursive because each type is rendered on its own.
+fn shared_types<'a>(types: impl IntoIterator<Item = Related<'a>>) -> Vec<Related<'a>> {
+    let mut result: Vec<Related> = vec![];
+
+    for related in types {
+        let name = related.qualified_name();
+
+        if result.iter().all(|other| other.qualified_name() != name) {
+            result.push(Related {
+                ty: related.ty,
+                recursive: vec![],
+            });
+        }
+    }
+
+    let names: Vec<_> = result.iter().map(Related::qualified_name).collect();
+
+    result.retain(|related| {
+        let name = related.qualified_name();
+        !names
+            .iter()
+            .any(|scope| *scope != name && is_in_scope(name, scope))
+    });
+
+    result
+}
+
+/// Descend field message types starting from `ty` and return `ty` followed by all types reachable
+/// from it, each listed once.
+///
+/// Types nested in a listed type are not returned themselves because they are rendered as part of
+/// it, however their fields are descended as well. Nested field types are therefore reached through
+/// their outermost enclosing type. `file` is only used to report unresolved types.
+fn gather_types<'a>(
+    ty: &'a proto::Types<'a>,
+    types: &'a proto::AllTypes,
+    file: &str,
+) -> proto::Result<Vec<Related<'a>>> {
     let mut result = vec![];
+    let mut visited = HashSet::new();
+    let mut stack = vec![];
+    visit_type(ty, types, file, &mut visited, &mut stack, &mut result)?;
+    Ok(result)
+}
 
-    if let proto::Types::Message(ty) = ty {
-        for field in &ty.fields {
-            if let proto::FieldType::Custom(custom) = &field.ty {
-                // We should be able to unwrap here but we get false package names for nested message
-                // types, so work around for now.
-                if let Some(custom_types) = types.get(custom.name.package) {
-                    for custom_type in custom_types {
-                        if custom_type.has_name(field.ty.name()) && !result.contains(&custom_type) {
-                            result.push(custom_type);
-                            result.append(&mut gather_types(custom_type, types));
-                        }
-                    }
+/// List `ty` in `result` and descend its fields depth-first.
+///
+/// `visited` contains names of all types listed so far and `stack` the names of the types that
+/// are currently being descended, i.e. where a cycle can close.
+fn visit_type<'a>(
+    ty: &'a proto::Types<'a>,
+    types: &'a proto::AllTypes,
+    file: &str,
+    visited: &mut HashSet<&'a str>,
+    stack: &mut Vec<&'a str>,
+    result: &mut Vec<Related<'a>>,
+) -> proto::Result<()> {
+    let idx = result.len();
+
+    visited.insert(ty.full_name());
+    result.push(Related {
+        ty,
+        recursive: vec![],
+    });
+
+    let proto::Types::Message(message) = ty else {
+        return Ok(());
+    };
+
+    stack.push(ty.full_name());
+
+    for (parent, field) in message.all_fields() {
+        for custom in field.ty.custom_types() {
+            let custom_type = types.get(custom.name.original).ok_or_else(|| {
+                let element = format!("{}.{}", &parent.full_name[1..], field.name);
+                let reason = format!("unresolved type `{}`", custom.name.original);
+                proto::Error::new(file, &element, reason)
+            })?;
+
+            let name = custom_type.full_name();
+
+            // Fields of nested types may close a cycle through the rendered type enclosing them.
+            let enclosing =
+                is_in_scope(&parent.full_name, name) && is_in_scope(name, ty.full_name());
+
+            if stack.contains(&name) || enclosing {
+                if !result[idx]
+                    .recursive
+                    .iter()
+                    .any(|f| std::ptr::eq(*f, field))
+                {
+                    result[idx].recursive.push(field);
                 }
+            } else if !visited.iter().any(|scope| is_in_scope(name, scope)) {
+                let outermost = outermost_type(custom_type, types);
+                visit_type(outermost, types, file, visited, stack, result)?;
             }
         }
     }
 
-    result
+    stack.pop();
+    Ok(())
 }
 
-impl<'a> Method<'a> {
-    fn from(value: proto::Method<'a>, types: &'a proto::AllTypes) -> Self {
-        let mut additional = gather_types(value.input_type, types);
-        let mut input_types = vec![value.input_type];
-        input_types.append(&mut additional);
+/// Return the outermost message type enclosing `ty` or `ty` itself if it is not nested.
+fn outermost_type<'a>(
+    ty: &'a proto::Types<'a>,
+    types: &'a proto::AllTypes,
+) -> &'a proto::Types<'a> {
+    let name = ty.full_name();
 
-        let mut additional = gather_types(value.output_type, types);
-        let mut output_types = vec![value.output_type];
-        output_types.append(&mut additional);
+    name.match_indices('.')
+        .find_map(|(idx, _)| types.get(&name[..idx]))
+        .unwrap_or(ty)
+}
 
+impl<'a> Related<'a> {
+    /// Return declaration of the type with markup of `syntax`, placing anchors of the declared
+    /// types not yet in `anchors` if set.
+    fn declaration(
+        &self,
+        syntax: impl Syntax,
+        anchors: Option<&Anchors>,
+    ) -> askama::Result<String> {
+        let syntax: &dyn Syntax = &syntax;
+
+        match self.ty {
+            proto::Types::Message(ty) => Message {
+                ty,
+                recursive: &self.recursive,
+                syntax,
+                anchors,
+            }
+            .render(),
+            proto::Types::Enum(ty) => Enum {
+                ty,
+                depth: 0,
+                syntax,
+                anchors,
+            }
+            .render(),
+        }
+    }
+
+    /// Return package-qualified name of the type.
+    fn qualified_name(&self) -> &'a str {
+        &self.ty.full_name()[1..]
+    }
+
+    fn model(&self, page: &Page, anchors: &Anchors) -> askama::Result<model::Type<'a>> {
+        let (kind, definition, linked_definition) = match self.ty {
+            proto::Types::Message(m) => (
+                model::Kind::Message(model::Message::from(m, &self.recursive)),
+                self.declaration(Code, None)?,
+                self.declaration(page, None)?,
+            ),
+            proto::Types::Enum(e) => (
+                model::Kind::Enum(model::Enum::from(e)),
+                self.declaration(Code, None)?,
+                self.declaration(page, None)?,
+            ),
+        };
+
+        let used_by = page
+            .used_by(self)
+            .into_iter()
+            .map(|(name, url)| model::Link { name, url })
+            .collect();
+
+        // Table captions carry the same anchors as listed for the type.
+        let table = match page.options.style {
+            Style::Table => Some(page.table(self, &anchors.clone())?),
+            Style::Code => None,
+        };
+
+        Ok(model::Type {
+            kind,
+            definition: definition.trim_matches('\n').to_string(),
+            linked_definition: linked_definition.trim().to_string(),
+            table,
+            anchors: first_anchors(anchors, links::type_names(self.ty))
+                .into_iter()
+                .map(ToString::to_string)
+                .collect(),
+            used_by,
+        })
+    }
+}
+
+impl<'a, 'b> Message<'a, 'b> {
+    /// Return block of nested message type `ty`.
+    fn nested(&self, ty: &'b proto::MessageType<'a>) -> Self {
+        Self {
+            ty,
+            recursive: self.recursive,
+            syntax: self.syntax,
+            anchors: self.anchors,
+        }
+    }
+
+    /// Return block of enum type `ty` nested in this message type.
+    fn nested_enum(&self, ty: &'b proto::EnumType<'a>) -> Enum<'a, 'b> {
+        Enum {
+            ty,
+            depth: self.ty.depth + 1,
+            syntax: self.syntax,
+            anchors: self.anchors,
+        }
+    }
+
+    /// Return `true` if `field` closes a cycle, which is noted in its declaration.
+    fn is_recursive(&self, field: &proto::Field) -> bool {
+        self.recursive.iter().any(|f| std::ptr::eq(*f, field))
+    }
+
+    /// Return name of the message type, carrying its anchor on its first declaration.
+    fn name(&self) -> String {
+        let id = self
+            .anchors
+            .and_then(|a| first_anchor(a, &self.ty.full_name));
+        self.syntax.type_name(self.ty.name, id)
+    }
+
+    fn field_type(&self, field: &proto::Field) -> String {
+        self.syntax.field_type(&field.ty)
+    }
+}
+
+impl<'b> Enum<'_, 'b> {
+    /// Return name of the enum type, carrying its anchor on its first declaration.
+    fn name(&self) -> String {
+        let id = self
+            .anchors
+            .and_then(|a| first_anchor(a, &self.ty.full_name));
+        self.syntax.type_name(self.ty.name, id)
+    }
+}
+
+/// Return caption of the table of the type with fully qualified `name`, carrying the anchor
+/// linked to by field types if this is its first table.
+fn table_caption(anchors: &Anchors, name: &str) -> String {
+    match first_anchor(anchors, name) {
+        Some(id) => format!("<a id=\"{id}\"></a>**{id}**"),
+        None => format!("**{}**", &name[1..]),
+    }
+}
+
+/// Return `ty` as code with message and enum types linking to their tables on `page`.
+fn table_type(ty: &proto::FieldType, page: &Page) -> String {
+    match ty {
+        proto::FieldType::WellKnown(_) => format!("`{ty}`"),
+        proto::FieldType::Custom(custom) => {
+            format!("[`{ty}`]({})", page.url(&custom.name.original[1..]))
+        }
+        proto::FieldType::Map(key, value) => {
+            format!(
+                "map&lt;{}, {}&gt;",
+                table_type(key, page),
+                table_type(value, page)
+            )
+        }
+    }
+}
+
+impl<'a, 'b> MessageTable<'a, 'b> {
+    fn caption(&self) -> String {
+        table_caption(self.anchors, &self.ty.full_name)
+    }
+
+    /// Return table of nested message type `ty`.
+    fn nested(&self, ty: &'b proto::MessageType<'a>) -> Self {
         Self {
+            ty,
+            page: self.page,
+            anchors: self.anchors,
+        }
+    }
+
+    /// Return table of enum type `ty` nested in this message type.
+    fn nested_enum(&self, ty: &'b proto::EnumType<'a>) -> EnumTable<'a, 'b> {
+        EnumTable {
+            ty,
+            anchors: self.anchors,
+        }
+    }
+
+    fn field_type(&self, field: &proto::Field) -> String {
+        table_type(&field.ty, self.page)
+    }
+
+    /// Return `optional`, `repeated` or the enclosing `oneof` of `field`.
+    fn label(&self, field: &proto::Field) -> String {
+        let oneof = field
+            .oneof_index
+            .and_then(|index| self.ty.oneofs.iter().find(|oneof| oneof.index == index));
+
+        match oneof {
+            Some(oneof) => format!("oneof `{}`", oneof.name),
+            None if field.optional => "`optional`".to_string(),
+            None if field.repeated => "`repeated`".to_string(),
+            None => String::new(),
+        }
+    }
+
+    #[allow(clippy::unused_self)]
+    fn description(&self, field: &proto::Field) -> String {
+        comments(field.leading_comments, field.trailing_comments)
+    }
+}
+
+impl EnumTable<'_, '_> {
+    fn caption(&self) -> String {
+        table_caption(self.anchors, &self.ty.full_name)
+    }
+
+    #[allow(clippy::unused_self)]
+    fn description(&self, value: &proto::EnumValue) -> String {
+        comments(value.leading_comments, value.trailing_comments)
+    }
+}
+
+/// Return leading and trailing comments as separate paragraphs.
+fn comments(leading: &str, trailing: &str) -> String {
+    match (leading.trim().is_empty(), trailing.trim().is_empty()) {
+        (false, false) => format!("{leading}\n\n{trailing}"),
+        (false, true) => leading.to_string(),
+        _ => trailing.to_string(),
+    }
+}
+
+impl<'a> Method<'a> {
+    /// Construct method of service with package-qualified name `service` declared in `file`.
+    fn from(
+        value: proto::Method<'a>,
+        service: &str,
+        types: &'a proto::AllTypes,
+        file: &str,
+    ) -> proto::Result<Self> {
+        let input_types = gather_types(value.input_type, types, file)?;
+        let output_types = gather_types(value.output_type, types, file)?;
+
+        Ok(Self {
             name: value.name,
+            id: format!("{service}.{}", value.name),
             call_type: value.call_type,
             deprecated: value.deprecated,
             description: value.description,
+            input_name: &value.input_type.full_name()[1..],
+            output_name: &value.output_type.full_name()[1..],
             input_types,
             output_types,
-        }
+        })
+    }
+}
+
+impl<'a> Method<'a> {
+    fn model(&self, page: &Page, anchors: &Anchors) -> askama::Result<model::Method<'a>> {
+        let types = |types: &[Related<'a>]| models(types, |ty| ty.model(page, anchors));
+
+        Ok(model::Method {
+            name: self.name,
+            id: self.id.clone(),
+            call_type: self.call_type.to_string(),
+            description: self.description,
+            deprecated: self.deprecated,
+            input_name: self.input_name,
+            output_name: self.output_name,
+            input_url: page.url(self.input_name),
+            output_url: page.url(self.output_name),
+            input_types: types(&self.input_types)?,
+            output_types: types(&self.output_types)?,
+        })
     }
 }
 
 impl<'a> Service<'a> {
-    fn from(value: proto::Service<'a>, types: &'a proto::AllTypes) -> Self {
+    fn from(value: proto::Service<'a>, types: &'a proto::AllTypes) -> proto::Result<Self> {
+        let full_name = proto::qualified_name(value.package, value.name);
+
         let (deprecated_methods, methods) = value
             .methods
             .into_iter()
-            .map(|m| Method::from(m, types))
+            .map(|m| Method::from(m, &full_name, types, value.file))
+            .collect::<proto::Result<Vec<_>>>()?
+            .into_iter()
             .partition(|m| m.deprecated);
 
-        Self {
+        Ok(Self {
             name: value.name,
             package: value.package,
+            full_name,
             description: value.description,
             deprecated: value.deprecated,
             methods,
             deprecated_methods,
-        }
+        })
+    }
+}
+
+impl<'a> Service<'a> {
+    fn model(&self, page: &Page, anchors: &Anchors) -> askama::Result<model::Service<'a>> {
+        let methods = |methods: &[Method<'a>]| models(methods, |m| m.model(page, anchors));
+
+        Ok(model::Service {
+            name: self.name,
+            package: self.package,
+            full_name: self.full_name.clone(),
+            description: self.description,
+            deprecated: self.deprecated,
+            methods: methods(&self.methods)?,
+            deprecated_methods: methods(&self.deprecated_methods)?,
+        })
     }
 }
 
+/// Convert all `items` with `model`.
+fn models<T, M>(items: &[T], model: impl Fn(&T) -> askama::Result<M>) -> askama::Result<Vec<M>> {
+    items.iter().map(model).collect()
+}
+
 impl<'a> Page<'a> {
     pub fn from(
         services: Vec<proto::Service<'a>>,
+        documented: Vec<&'a proto::Types<'a>>,
         types: &'a proto::AllTypes,
         options: &'a Options,
-    ) -> Self {
-        let services = services
+    ) -> proto::Result<Self> {
+        let mut services: Vec<Service> = services
             .into_iter()
             .map(|s| Service::from(s, types))
+            .collect::<proto::Result<_>>()?;
+
+        let mut types: Vec<_> = documented
+            .into_iter()
+            .map(|ty| Related {
+                ty,
+                recursive: vec![],
+            })
+            .collect();
+
+        // Methods only link to their types which are documented once after the services.
+        if options.types_section != TypesSection::Inline {
+            let used = services
+                .iter_mut()
+                .flat_map(|service| {
+                    service
+                        .methods
+                        .iter_mut()
+                        .chain(&mut service.deprecated_methods)
+                })
+                .flat_map(|method| {
+                    std::mem::take(&mut method.input_types)
+                        .into_iter()
+                        .chain(std::mem::take(&mut method.output_types))
+                })
+                .collect::<Vec<_>>();
+
+            types = shared_types(types.into_iter().chain(used));
+        }
+
+        Ok(Self {
+            services,
+            types,
+            options,
+            anchors: RefCell::default(),
+            links: None,
+            name: "",
+        })
+    }
+
+    /// Return the types documented on their own.
+    pub fn types(&self) -> impl Iterator<Item = &'a proto::Types<'a>> + '_ {
+        self.types.iter().map(|related| related.ty)
+    }
+
+    /// Remove types documented on their own, leaving the services.
+    #[must_use]
+    pub fn without_types(mut self) -> Self {
+        self.types.clear();
+        self
+    }
+
+    /// Resolve links to other pages with `links`, this page being named `name`.
+    #[must_use]
+    pub fn with_links(mut self, links: &'a Links, name: &'a str) -> Self {
+        self.links = Some(links);
+        self.name = name;
+        self
+    }
+
+    /// Return all types documented on this page in the order of their declarations.
+    fn related(&self) -> impl Iterator<Item = &Related<'a>> {
+        self.services
+            .iter()
+            .flat_map(|service| service.methods.iter().chain(&service.deprecated_methods))
+            .flat_map(|method| method.input_types.iter().chain(&method.output_types))
+            .chain(&self.types)
+    }
+
+    /// Return link to the type with package-qualified `name` on this or another page.
+    fn url(&self, name: &str) -> String {
+        self.links
+            .map_or_else(|| format!("#{name}"), |links| links.url(name, self.name))
+    }
+
+    /// Return `true` unless another page is linked to for the type with package-qualified `name`.
+    fn is_linked_here(&self, name: &str) -> bool {
+        self.links
+            .and_then(|links| links.page(name))
+            .is_none_or(|page| page == self.name)
+    }
+
+    /// Return names and links of the methods and message types using `related`.
+    fn used_by(&self, related: &Related) -> Vec<(String, String)> {
+        let Some(links) = self.links else {
+            return vec![];
+        };
+
+        links
+            .users(related.qualified_name())
+            .iter()
+            .map(|user| match user {
+                User::Method { id, page } if page == self.name => {
+                    (format!("{id}()"), format!("#{id}"))
+                }
+                User::Method { id, page } => (format!("{id}()"), format!("{page}#{id}")),
+                User::Message(name) => (name.clone(), self.url(name)),
+            })
+            .collect()
+    }
+
+    /// Return anchors of `related` and its nested types on their first declaration.
+    fn type_anchors(&self, related: &Related) -> String {
+        let anchors = first_anchors(&self.anchors, links::type_names(related.ty));
+
+        if anchors.is_empty() {
+            return String::new();
+        }
+
+        let mut result: String = anchors
+            .iter()
+            .map(|id| format!("<a id=\"{id}\"></a>"))
             .collect();
 
-        Self { services, options }
+        result.push('\n');
+        result
+    }
+
+    /// Return tables of `related` with field types linking to their tables, carrying anchors of
+    /// the types not yet in `anchors`.
+    fn table(&self, related: &Related, anchors: &Anchors) -> askama::Result<String> {
+        match related.ty {
+            proto::Types::Message(ty) => MessageTable {
+                ty,
+                page: self,
+                anchors,
+            }
+            .render(),
+            proto::Types::Enum(ty) => EnumTable { ty, anchors }.render(),
+        }
+    }
+
+    /// Return model of this page passed to user-supplied templates.
+    pub fn model(&self) -> askama::Result<model::Page<'a>> {
+        let anchors = Anchors::default();
+
+        Ok(model::Page {
+            services: models(&self.services, |s| s.model(self, &anchors))?,
+            types: models(&self.types, |ty| ty.model(self, &anchors))?,
+        })
+    }
+}
+
+/// Declarations in raw HTML blocks of Markdown pages whose field types link to their types on
+/// this or another page.
+impl Syntax for Page<'_> {
+    fn comment(&self, comment: &str) -> String {
+        askama::MarkupDisplay::new_unsafe(comment, askama::Html).to_string()
+    }
+
+    fn field_type(&self, ty: &proto::FieldType) -> String {
+        match ty {
+            proto::FieldType::WellKnown(_) => ty.to_string(),
+            proto::FieldType::Custom(custom) => {
+                format!(
+                    "<a href=\"{}\">{ty}</a>",
+                    self.url(&custom.name.original[1..])
+                )
+            }
+            proto::FieldType::Map(key, value) => format!(
+                "map&lt;{}, {}&gt;",
+                self.field_type(key),
+                self.field_type(value)
+            ),
+        }
+    }
+}
+
+impl Summary {
+    /// Construct summary nesting `chapters` in draft chapters of their packages.
+    ///
+    /// Services and their methods are nested in the chapters as draft chapters as well because
+    /// mdBook only supports links to entire pages.
+    pub fn from(chapters: Vec<Chapter>) -> Self {
+        let mut packages: Vec<(&str, Vec<Chapter>)> = vec![];
+
+        for chapter in chapters {
+            match packages.iter_mut().find(|(p, _)| *p == chapter.package) {
+                Some((_, chapters)) => chapters.push(chapter),
+                None => packages.push((chapter.package, vec![chapter])),
+            }
+        }
+
+        let mut items = vec![];
+
+        for (package, chapters) in packages {
+            // Files without package are listed at the top level.
+            let depth = usize::from(!package.is_empty());
+
+            if !package.is_empty() {
+                items.push(SummaryItem {
+                    depth: 0,
+                    title: package.to_string(),
+                    link: String::new(),
+                });
+            }
+
+            for chapter in chapters {
+                // All files share the same page when generating a single page.
+                if items.iter().any(|item| item.link == chapter.page) {
+                    continue;
+                }
+
+                items.push(SummaryItem {
+                    depth,
+                    title: chapter.file.to_string(),
+                    link: chapter.page,
+                });
+
+                for (service, methods) in chapter.services {
+                    items.push(SummaryItem {
+                        depth: depth + 1,
+                        title: service,
+                        link: String::new(),
+                    });
+
+                    items.extend(methods.into_iter().map(|method| SummaryItem {
+                        depth: depth + 2,
+                        title: format!("{method}()"),
+                        link: String::new(),
+                    }));
+                }
+            }
+        }
+
+        Self { items }
     }
 }
 
@@ -132,11 +921,310 @@ pub mod filters {
 
         Ok(result)
     }
+
+    /// Remove indentation common to all non-empty lines of `s` as well as surrounding blank
+    /// lines, e.g. the space following `//` of comments.
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn unindent<T: std::fmt::Display>(s: T) -> askama::Result<String> {
+        let s = s.to_string();
+        let indent = s
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+
+        Ok(s.lines()
+            .map(|line| line.get(indent..).unwrap_or_default().trim_end())
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim_matches('\n')
+            .to_string())
+    }
+
+    /// Underline `s` as reStructuredText section title of `level`, starting with the overlined
+    /// document title at level 0.
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn heading<T: std::fmt::Display>(s: T, level: usize) -> askama::Result<String> {
+        let s = s.to_string();
+        let line = ["=", "=", "-", "~"][level.min(3)].repeat(s.chars().count());
+
+        if level == 0 {
+            Ok(format!("{line}\n{s}\n{line}"))
+        } else {
+            Ok(format!("{s}\n{line}"))
+        }
+    }
+
+    /// Escape braces and angle brackets in `s` which MDX would parse as expressions and JSX.
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn mdx_escape<T: std::fmt::Display>(s: T) -> askama::Result<String> {
+        let s = s.to_string();
+        let mut escaped = String::with_capacity(s.len());
+
+        for c in s.chars() {
+            if matches!(c, '{' | '}' | '<') {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+
+        Ok(escaped)
+    }
+
+    /// Render `s` as Markdown table cell, joining lines and separating paragraphs by line breaks.
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn table_cell<T: std::fmt::Display>(s: T) -> askama::Result<String> {
+        let paragraphs: Vec<_> = unindent(s)?
+            .split("\n\n")
+            .map(|paragraph| {
+                let lines: Vec<_> = paragraph
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .collect();
+                lines.join(" ")
+            })
+            .filter(|paragraph| !paragraph.is_empty())
+            .collect();
+
+        Ok(paragraphs.join("<br><br>").replace('|', "\\|"))
+    }
+
+    /// Escape `s` for use in a TOML basic string or a double-quoted YAML string.
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn toml_escape<T: std::fmt::Display>(s: T) -> askama::Result<String> {
+        Ok(s.to_string().replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    /// Prepend each line in `s` with two times `depth` spaces.
+    #[allow(clippy::unnecessary_wraps, clippy::trivially_copy_pass_by_ref)]
+    pub fn lead_lines<T: std::fmt::Display>(s: T, depth: &usize) -> askama::Result<String> {
+        let lead = lead(depth)?;
+
+        Ok(s.to_string()
+            .lines()
+            .map(|s| format!("{lead}{s}"))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::filters::render_multiline_comment;
+    use super::filters::{lead_lines, mdx_escape, render_multiline_comment, table_cell, unindent};
+    use super::{gather_types, Chapter, Page, Summary};
+    use crate::{proto, Options, Style};
+    use askama::Template;
+    use prost_types::compiler::CodeGeneratorRequest;
+    use prost_types::field_descriptor_proto::Type;
+    use prost_types::{
+        DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+        FileDescriptorProto, SourceCodeInfo,
+    };
+
+    fn message(name: &str, fields: &[(&str, &str)]) -> DescriptorProto {
+        DescriptorProto {
+            name: Some(name.to_string()),
+            field: fields
+                .iter()
+                .enumerate()
+                .map(|(number, (name, type_name))| FieldDescriptorProto {
+                    name: Some((*name).to_string()),
+                    number: Some(i32::try_from(number).unwrap() + 1),
+                    r#type: Some(Type::Message.into()),
+                    type_name: Some((*type_name).to_string()),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn gather_recursive_types() {
+        let request = CodeGeneratorRequest {
+            proto_file: vec![FileDescriptorProto {
+                package: Some("foo".to_string()),
+                message_type: vec![
+                    message("Node", &[("children", ".foo.Node"), ("a", ".foo.A")]),
+                    message("A", &[("b", ".foo.B")]),
+                    message("B", &[("a", ".foo.A"), ("node", ".foo.Node")]),
+                ],
+                source_code_info: Some(SourceCodeInfo::default()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let types = proto::get_types(&request).unwrap();
+        let related = gather_types(&types[".foo.Node"], &types, "foo.proto").unwrap();
+        let names = related.iter().map(|r| r.ty.full_name()).collect::<Vec<_>>();
+        assert_eq!(names, [".foo.Node", ".foo.A", ".foo.B"]);
+
+        let recursive = related
+            .iter()
+            .map(|r| r.recursive.iter().map(|f| f.name).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(recursive, [vec!["children"], vec![], vec!["a", "node"]]);
+    }
+
+    #[test]
+    fn gather_types_enclosing_nested_type() {
+        let mut outer = message("Outer", &[("inner", ".foo.Outer.Inner")]);
+        outer
+            .nested_type
+            .push(message("Inner", &[("parent", ".foo.Outer")]));
+
+        let request = CodeGeneratorRequest {
+            proto_file: vec![FileDescriptorProto {
+                package: Some("foo".to_string()),
+                message_type: vec![outer],
+                source_code_info: Some(SourceCodeInfo::default()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let types = proto::get_types(&request).unwrap();
+
+        // The enclosing type is rendered along with the nested one rather than referenced above.
+        let related = gather_types(&types[".foo.Outer.Inner"], &types, "foo.proto").unwrap();
+        let names = related.iter().map(|r| r.ty.full_name()).collect::<Vec<_>>();
+        assert_eq!(names, [".foo.Outer.Inner", ".foo.Outer"]);
+
+        let recursive = related
+            .iter()
+            .map(|r| r.recursive.iter().map(|f| f.name).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(recursive, [vec![], vec!["inner", "parent"]]);
+
+        let related = gather_types(&types[".foo.Outer"], &types, "foo.proto").unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].recursive.len(), 1);
+        assert_eq!(related[0].recursive[0].name, "parent");
+    }
+
+    #[test]
+    fn gather_types_nested_before_enclosing() {
+        let mut status = message("Status", &[("detail", ".foo.Status.Detail")]);
+        status
+            .nested_type
+            .push(message("Detail", &[("status", ".foo.Status")]));
+
+        let request = CodeGeneratorRequest {
+            proto_file: vec![FileDescriptorProto {
+                package: Some("foo".to_string()),
+                message_type: vec![
+                    message("Req", &[("d", ".foo.Status.Detail"), ("s", ".foo.Status")]),
+                    status,
+                ],
+                source_code_info: Some(SourceCodeInfo::default()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let types = proto::get_types(&request).unwrap();
+
+        // The nested type is rendered as part of its enclosing type rather than on its own.
+        let related = gather_types(&types[".foo.Req"], &types, "foo.proto").unwrap();
+        let names = related.iter().map(|r| r.ty.full_name()).collect::<Vec<_>>();
+        assert_eq!(names, [".foo.Req", ".foo.Status"]);
+
+        let recursive = related
+            .iter()
+            .map(|r| r.recursive.iter().map(|f| f.name).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(recursive, [vec![], vec!["status"]]);
+    }
+
+    #[test]
+    fn render_nested_enums() {
+        let enumeration = |name: &str, values: &[&str]| EnumDescriptorProto {
+            name: Some(name.to_string()),
+            value: values
+                .iter()
+                .enumerate()
+                .map(|(number, name)| EnumValueDescriptorProto {
+                    name: Some((*name).to_string()),
+                    number: Some(i32::try_from(number).unwrap()),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut inner = message("Inner", &[]);
+        inner.enum_type.push(enumeration("Kind", &["A", "B"]));
+        let mut outer = message("Outer", &[("inner", ".foo.Outer.Inner")]);
+        outer.nested_type.push(inner);
+        outer.enum_type.push(enumeration("State", &["ON"]));
+
+        let request = CodeGeneratorRequest {
+            proto_file: vec![FileDescriptorProto {
+                name: Some("foo.proto".to_string()),
+                package: Some("foo".to_string()),
+                message_type: vec![outer],
+                source_code_info: Some(SourceCodeInfo::default()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let types = proto::get_types(&request).unwrap();
+        let documented = proto::get_file_types(&request, "foo.proto", &types).unwrap();
+        let options = Options::default();
+        let page = Page::from(vec![], documented, &types, &options).unwrap();
+
+        let block = [
+            "<pre><code class=\"language-protobuf\">message Outer {",
+            "  message Inner {",
+            "    enum Kind {",
+            "      A = 0;",
+            "      B = 1;",
+            "    }",
+            "  }",
+            "  enum State {",
+            "    ON = 0;",
+            "  }",
+            "  <a href=\"#foo.Outer.Inner\">Inner</a> inner = 1;",
+            "}</code></pre>",
+        ];
+        assert!(page.render().unwrap().contains(&block.join("\n")));
+    }
+
+    #[test]
+    fn render_tables() {
+        let request = CodeGeneratorRequest {
+            proto_file: vec![FileDescriptorProto {
+                name: Some("foo.proto".to_string()),
+                package: Some("foo".to_string()),
+                message_type: vec![
+                    message("Node", &[("children", ".foo.Node"), ("a", ".foo.A")]),
+                    message("A", &[]),
+                ],
+                source_code_info: Some(SourceCodeInfo::default()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let types = proto::get_types(&request).unwrap();
+        let documented = proto::get_file_types(&request, "foo.proto", &types).unwrap();
+        let options = Options::default().style(Style::Table);
+        let page = Page::from(vec![], documented, &types, &options).unwrap();
+        let content = page.render().unwrap();
+
+        assert!(content.contains(
+            "<a id=\"foo.Node\"></a>**foo.Node**\n\n\
+             | Field | Type | Number | Label | Description |\n\
+             | --- | --- | --- | --- | --- |\n\
+             | `children` | [`Node`](#foo.Node) | 1 |  |  |\n\
+             | `a` | [`A`](#foo.A) | 2 |  |  |\n"
+        ));
+        assert!(content.contains("<a id=\"foo.A\"></a>**foo.A**\n"));
+    }
 
     #[test]
     fn render_multiline_comments() {
@@ -145,4 +1233,62 @@ mod tests {
             "//foo\n//bar"
         );
     }
+
+    #[test]
+    fn render_summary() {
+        let chapter = |file, package, page: &str| Chapter {
+            file,
+            package,
+            page: page.to_string(),
+            services: vec![],
+        };
+
+        let mut foo = chapter("a/foo.proto", "a", "a.foo.proto.md");
+        foo.services = vec![("a.Foo".to_string(), vec!["Get", "Put"])];
+
+        let summary = Summary::from(vec![
+            foo,
+            chapter("bar.proto", "", "bar.proto.md"),
+            chapter("a/baz.proto", "a", "a.baz.proto.md"),
+        ]);
+
+        assert_eq!(
+            summary.render().unwrap(),
+            "# Summary\n\n- [a]()\n  - [a/foo.proto](a.foo.proto.md)\n    - [a.Foo]()\n      \
+             - [Get()]()\n      - [Put()]()\n  - [a/baz.proto](a.baz.proto.md)\n\
+             - [bar.proto](bar.proto.md)"
+        );
+    }
+
+    #[test]
+    fn lead_multiple_lines() {
+        assert_eq!(
+            lead_lines("//foo\n//bar", &2).unwrap(),
+            "    //foo\n    //bar"
+        );
+    }
+
+    #[test]
+    fn unindent_comments() {
+        assert_eq!(
+            unindent(" foo\n   bar\n\n baz\n").unwrap(),
+            "foo\n  bar\n\nbaz"
+        );
+    }
+
+    #[test]
+    fn render_table_cells() {
+        assert_eq!(
+            table_cell(" foo\n bar\n\n\n baz | qux\n").unwrap(),
+            "foo bar<br><br>baz \\| qux"
+        );
+    }
+
+    #[test]
+    fn escape_mdx() {
+        assert_eq!(
+            mdx_escape("returns {id} if a < b").unwrap(),
+            "returns \\{id\\} if a \\< b"
+        );
+    }
 }
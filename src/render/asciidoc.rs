@@ -0,0 +1,76 @@
+//! AsciiDoc pages for Asciidoctor and Antora with cross-referenced declarations.
+
+use super::{filters, Anchors, Page, Syntax};
+use crate::proto;
+use askama::Template;
+use std::cell::RefCell;
+
+#[derive(Template)]
+#[template(path = "page.adoc", escape = "none")]
+struct AsciiDocPage<'a, 'b> {
+    title: &'b str,
+    page: &'b Page<'a>,
+    anchors: Anchors,
+}
+
+/// Declarations with inline anchors whose field types reference the declarations of their types.
+struct AsciiDoc;
+
+impl Syntax for AsciiDoc {
+    fn type_name(&self, name: &str, id: Option<&str>) -> String {
+        match id {
+            Some(id) => format!("[[{id}]]{name}"),
+            None => name.to_string(),
+        }
+    }
+
+    fn field_type(&self, ty: &proto::FieldType) -> String {
+        match ty {
+            proto::FieldType::WellKnown(_) => ty.to_string(),
+            proto::FieldType::Custom(custom) => format!("<<{},{ty}>>", &custom.name.original[1..]),
+            proto::FieldType::Map(key, value) => {
+                format!("map<{}, {}>", self.field_type(key), self.field_type(value))
+            }
+        }
+    }
+}
+
+/// Render `page` as AsciiDoc document titled `title`.
+pub fn render(page: &Page, title: &str) -> askama::Result<String> {
+    AsciiDocPage {
+        title,
+        page,
+        anchors: RefCell::default(),
+    }
+    .render()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::render::Page;
+    use crate::{fixtures, proto, Options};
+    use prost_types::MethodOptions;
+
+    #[test]
+    fn reference_declarations() {
+        let mut request = fixtures::tree();
+        request.proto_file[0].service[0].method[0].options = Some(MethodOptions {
+            deprecated: Some(true),
+            ..Default::default()
+        });
+
+        let types = proto::get_types(&request).unwrap();
+        let services = proto::get_services(&request, "foo.proto", &types).unwrap();
+        let options = Options::default();
+        let page = Page::from(services, vec![], &types, &options).unwrap();
+        let adoc = render(&page, "API").unwrap();
+
+        assert!(adoc.starts_with("= API\n\n[[foo.Tree]]\n== foo.Tree\n"));
+        assert!(adoc.contains("[[foo.Tree.Get]]\n=== Get()\n"));
+        assert!(adoc.contains("WARNING: This method is deprecated."));
+        assert!(adoc.contains("*Input:* <<foo.Node,`foo.Node`>>"));
+        assert_eq!(adoc.matches("[[foo.Node]]").count(), 1);
+        assert!(adoc.contains("message [[foo.Node]]Node {\n  <<foo.Node,Node>> next = 1;"));
+    }
+}
@@ -0,0 +1,88 @@
+//! Self-contained HTML pages with server-side highlighted and cross-linked declarations.
+
+use super::{Anchors, Page, Syntax};
+use crate::proto;
+use askama::Template;
+use std::cell::RefCell;
+
+#[derive(Template)]
+#[template(path = "page.html")]
+struct HtmlPage<'a, 'b> {
+    title: &'b str,
+    page: &'b Page<'a>,
+    anchors: Anchors,
+}
+
+/// Highlighted declarations whose field types link to the declarations of their types.
+struct Html;
+
+impl Syntax for Html {
+    fn keyword(&self, word: &str) -> String {
+        format!("<span class=\"kw\">{word}</span>")
+    }
+
+    fn comment(&self, comment: &str) -> String {
+        let comment = askama::MarkupDisplay::new_unsafe(comment, askama::Html);
+        format!("<span class=\"cm\">{comment}</span>")
+    }
+
+    fn number(&self, number: &i32) -> String {
+        format!("<span class=\"nu\">{number}</span>")
+    }
+
+    fn type_name(&self, name: &str, id: Option<&str>) -> String {
+        match id {
+            Some(id) => format!("<span class=\"ty\" id=\"{id}\">{name}</span>"),
+            None => format!("<span class=\"ty\">{name}</span>"),
+        }
+    }
+
+    fn field_type(&self, ty: &proto::FieldType) -> String {
+        match ty {
+            proto::FieldType::WellKnown(_) => self.keyword(&ty.to_string()),
+            proto::FieldType::Custom(custom) => {
+                let name = &custom.name.original[1..];
+                format!("<a class=\"ty\" href=\"#{name}\">{ty}</a>")
+            }
+            proto::FieldType::Map(key, value) => format!(
+                "{}&lt;{}, {}&gt;",
+                self.keyword("map"),
+                self.field_type(key),
+                self.field_type(value)
+            ),
+        }
+    }
+}
+
+/// Render `page` as HTML document titled `title`.
+pub fn render(page: &Page, title: &str) -> askama::Result<String> {
+    HtmlPage {
+        title,
+        page,
+        anchors: RefCell::default(),
+    }
+    .render()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::render::Page;
+    use crate::{fixtures, proto, Options};
+
+    #[test]
+    fn link_field_types() {
+        let request = fixtures::tree();
+
+        let types = proto::get_types(&request).unwrap();
+        let services = proto::get_services(&request, "foo.proto", &types).unwrap();
+        let options = Options::default();
+        let page = Page::from(services, vec![], &types, &options).unwrap();
+        let html = render(&page, "<API>").unwrap();
+
+        assert!(html.contains("<title>&lt;API&gt;</title>"));
+        assert!(html.contains("<h3 id=\"foo.Tree.Get\">Get()</h3>"));
+        assert_eq!(html.matches(" id=\"foo.Node\"").count(), 1);
+        assert!(html.contains("<a class=\"ty\" href=\"#foo.Node\">Node</a> next"));
+    }
+}
@@ -0,0 +1,204 @@
+//! Resolution of links to types and methods documented on other pages of the book.
+
+use super::{is_in_scope, Page};
+use crate::proto;
+use std::collections::HashMap;
+
+/// Element listed as user of a type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum User {
+    /// Method taking or returning the type, with the anchor of the method and its page.
+    Method { id: String, page: String },
+    /// Message type with a field of the type, identified by package-qualified name.
+    Message(String),
+}
+
+/// Pages documenting each type and the elements using it across all pages.
+#[derive(Debug, Default)]
+pub struct Links {
+    /// Pages documenting a type by package-qualified name, the one of its proto file first.
+    pages: HashMap<String, Vec<String>>,
+    users: HashMap<String, Vec<User>>,
+}
+
+/// Return package-qualified names of `ty` and all types nested in it.
+pub fn type_names<'a>(ty: &'a proto::Types) -> Vec<&'a str> {
+    fn nested_names<'a>(ty: &'a proto::MessageType, names: &mut Vec<&'a str>) {
+        for nested in &ty.nested {
+            names.push(&nested.full_name[1..]);
+            nested_names(nested, names);
+        }
+
+        names.extend(ty.enums.iter().map(|nested| &nested.full_name[1..]));
+    }
+
+    let mut names = vec![&ty.full_name()[1..]];
+
+    if let proto::Types::Message(ty) = ty {
+        nested_names(ty, &mut names);
+    }
+
+    names
+}
+
+impl Links {
+    /// Register the types and methods documented on `page` named `name`, preferring it for the
+    /// `declared` top-level types of its proto file.
+    pub fn add(&mut self, name: &str, page: &Page, declared: &[&proto::Types]) {
+        for related in page.related() {
+            for ty in type_names(related.ty) {
+                let pages = self.pages.entry(ty.to_string()).or_default();
+
+                if pages.iter().any(|page| page == name) {
+                    continue;
+                }
+
+                if declared
+                    .iter()
+                    .any(|scope| is_in_scope(ty, &scope.full_name()[1..]))
+                {
+                    pages.insert(0, name.to_string());
+                } else {
+                    pages.push(name.to_string());
+                }
+            }
+
+            if let proto::Types::Message(ty) = related.ty {
+                for (parent, field) in ty.all_fields() {
+                    for custom in field.ty.custom_types() {
+                        let user = User::Message(parent.full_name[1..].to_string());
+                        self.add_user(&custom.name.original[1..], user);
+                    }
+                }
+            }
+        }
+
+        for service in &page.services {
+            for method in service.methods.iter().chain(&service.deprecated_methods) {
+                let user = User::Method {
+                    id: method.id.clone(),
+                    page: name.to_string(),
+                };
+
+                self.add_user(method.input_name, user.clone());
+                self.add_user(method.output_name, user);
+            }
+        }
+    }
+
+    fn add_user(&mut self, ty: &str, user: User) {
+        let users = self.users.entry(ty.to_string()).or_default();
+
+        if !users.contains(&user) {
+            users.push(user);
+        }
+    }
+
+    /// Return link from page `from` to the type with package-qualified `name`, staying on `from`
+    /// if it documents the type as well.
+    pub fn url(&self, name: &str, from: &str) -> String {
+        match self.pages.get(name) {
+            Some(pages) if !pages.iter().any(|page| page == from) => {
+                format!("{}#{name}", pages[0])
+            }
+            _ => format!("#{name}"),
+        }
+    }
+
+    /// Return the page linked to for the type with package-qualified `name`.
+    pub fn page(&self, name: &str) -> Option<&str> {
+        self.pages.get(name).map(|pages| pages[0].as_str())
+    }
+
+    /// Return elements using the type with package-qualified `name`.
+    pub fn users(&self, name: &str) -> &[User] {
+        self.users.get(name).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Links, User};
+    use crate::render::Page;
+    use crate::{proto, Options};
+    use prost_types::compiler::CodeGeneratorRequest;
+    use prost_types::field_descriptor_proto::Type;
+    use prost_types::{
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, MethodDescriptorProto,
+        ServiceDescriptorProto, SourceCodeInfo,
+    };
+
+    #[test]
+    fn resolve_links_across_pages() {
+        let request = CodeGeneratorRequest {
+            proto_file: vec![
+                FileDescriptorProto {
+                    name: Some("foo.proto".to_string()),
+                    package: Some("foo".to_string()),
+                    message_type: vec![DescriptorProto {
+                        name: Some("Bar".to_string()),
+                        ..Default::default()
+                    }],
+                    source_code_info: Some(SourceCodeInfo::default()),
+                    ..Default::default()
+                },
+                FileDescriptorProto {
+                    name: Some("api.proto".to_string()),
+                    package: Some("api".to_string()),
+                    dependency: vec!["foo.proto".to_string()],
+                    message_type: vec![DescriptorProto {
+                        name: Some("Req".to_string()),
+                        field: vec![FieldDescriptorProto {
+                            name: Some("bar".to_string()),
+                            number: Some(1),
+                            r#type: Some(Type::Message.into()),
+                            type_name: Some(".foo.Bar".to_string()),
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    }],
+                    service: vec![ServiceDescriptorProto {
+                        name: Some("Api".to_string()),
+                        method: vec![MethodDescriptorProto {
+                            name: Some("Get".to_string()),
+                            input_type: Some(".api.Req".to_string()),
+                            output_type: Some(".foo.Bar".to_string()),
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    }],
+                    source_code_info: Some(SourceCodeInfo::default()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let types = proto::get_types(&request).unwrap();
+        let options = Options::default().all_types(true);
+        let mut links = Links::default();
+
+        // The page using the type comes first but the one declaring it is preferred.
+        for name in ["api.proto", "foo.proto"] {
+            let services = proto::get_services(&request, name, &types).unwrap();
+            let declared = proto::get_file_types(&request, name, &types).unwrap();
+            let page = Page::from(services, declared.clone(), &types, &options).unwrap();
+            links.add(&format!("{name}.md"), &page, &declared);
+        }
+
+        assert_eq!(links.url("foo.Bar", "api.proto.md"), "#foo.Bar");
+        assert_eq!(links.url("foo.Bar", "other.md"), "foo.proto.md#foo.Bar");
+        assert_eq!(links.url("api.Req", "foo.proto.md"), "api.proto.md#api.Req");
+        assert_eq!(links.url("foo.Unknown", "foo.proto.md"), "#foo.Unknown");
+        assert_eq!(
+            links.users("foo.Bar"),
+            [
+                User::Message("api.Req".to_string()),
+                User::Method {
+                    id: "api.Api.Get".to_string(),
+                    page: "api.proto.md".to_string()
+                }
+            ]
+        );
+    }
+}
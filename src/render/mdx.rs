@@ -0,0 +1,26 @@
+//! MDX pages for Docusaurus without raw HTML or expressions outside code blocks.
+
+use super::{filters, Page};
+use crate::proto;
+use askama::Template;
+
+/// YAML front matter of a Docusaurus page.
+pub struct FrontMatter<'a> {
+    pub id: String,
+    pub title: &'a str,
+    pub sidebar_position: usize,
+    /// Packages of the documented proto files.
+    pub tags: Vec<&'a str>,
+}
+
+#[derive(Template)]
+#[template(path = "page.mdx", escape = "none")]
+struct MdxPage<'a, 'b> {
+    front_matter: &'b FrontMatter<'b>,
+    page: &'b Page<'a>,
+}
+
+/// Render `page` as MDX document starting with `front_matter`.
+pub fn render(page: &Page, front_matter: &FrontMatter) -> askama::Result<String> {
+    MdxPage { front_matter, page }.render()
+}
@@ -0,0 +1,82 @@
+//! reStructuredText pages for Sphinx with labels referenced by methods.
+
+use super::{filters, first_anchor, Anchors, Page, Related};
+use crate::proto;
+use askama::Template;
+use std::cell::RefCell;
+
+#[derive(Template)]
+#[template(path = "page.rst", escape = "none")]
+struct RstPage<'a, 'b> {
+    title: &'b str,
+    page: &'b Page<'a>,
+    anchors: Anchors,
+}
+
+/// Root document linking all pages in its table of contents.
+#[derive(Template)]
+#[template(path = "index.rst", escape = "none")]
+pub struct Index<'a> {
+    pub title: &'a str,
+    /// Names of the linked documents, i.e. page names without extension.
+    pub documents: Vec<&'a str>,
+}
+
+/// Render `page` as reStructuredText document titled `title`.
+pub fn render(page: &Page, title: &str) -> askama::Result<String> {
+    RstPage {
+        title,
+        page,
+        anchors: RefCell::default(),
+    }
+    .render()
+}
+
+impl RstPage<'_, '_> {
+    /// Return label followed by a blank line for the first declaration of `related` and an empty
+    /// string for all further ones, or if another page carries the label.
+    fn label(&self, related: &Related) -> String {
+        first_anchor(&self.anchors, related.ty.full_name())
+            .filter(|name| self.page.is_linked_here(name))
+            .map_or_else(String::new, |name| format!(".. _{name}:\n\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, Index};
+    use crate::render::Page;
+    use crate::{fixtures, proto, Options};
+    use askama::Template;
+
+    #[test]
+    fn label_sections_and_types() {
+        let request = fixtures::tree();
+
+        let types = proto::get_types(&request).unwrap();
+        let services = proto::get_services(&request, "foo.proto", &types).unwrap();
+        let options = Options::default();
+        let page = Page::from(services, vec![], &types, &options).unwrap();
+        let rst = render(&page, "foo.proto").unwrap();
+
+        assert!(rst.starts_with(
+            "=========\nfoo.proto\n=========\n\n.. _foo.Tree:\n\nfoo.Tree\n========\n"
+        ));
+        assert!(rst.contains(".. _foo.Tree.Get:\n\nGet()\n-----\n"));
+        assert!(rst.contains("**Input:** :ref:`foo.Node <foo.Node>`"));
+        assert_eq!(rst.matches(".. _foo.Node:").count(), 1);
+        assert!(
+            rst.contains(".. code-block:: protobuf\n\n    message Node {\n      Node next = 1;")
+        );
+
+        let index = Index {
+            title: "API",
+            documents: vec!["foo.proto", "bar.proto"],
+        };
+
+        assert_eq!(
+            index.render().unwrap(),
+            "===\nAPI\n===\n\n.. toctree::\n   :maxdepth: 2\n\n   foo.proto\n   bar.proto\n"
+        );
+    }
+}
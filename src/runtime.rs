@@ -0,0 +1,139 @@
+//! Rendering with templates loaded at runtime from the `template_dir` option.
+//!
+//! Templates use the Jinja syntax of [MiniJinja](https://docs.rs/minijinja) and are looked up by
+//! name in the template directory, falling back to the built-in ones listed in [`BUILTIN`]. The
+//! page template `page.md` is rendered with the fields of [`model::Page`] and `options`, the
+//! others are included with `service`, `method` or `type` referring to the current element.
+
+use crate::{model, Options};
+use anyhow::{Context, Result};
+use minijinja::{context, Environment, Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// Built-in templates equivalent to the compiled-in ones.
+const BUILTIN: [(&str, &str); 5] = [
+    ("page.md", include_str!("../templates/runtime/page.md")),
+    (
+        "service.md",
+        include_str!("../templates/runtime/service.md"),
+    ),
+    ("method.md", include_str!("../templates/runtime/method.md")),
+    (
+        "message.md",
+        include_str!("../templates/runtime/message.md"),
+    ),
+    ("enum.md", include_str!("../templates/runtime/enum.md")),
+];
+
+/// Load template `name` from `dir` or the built-in templates if it does not exist in `dir`.
+fn load(dir: &Path, name: &str) -> Result<Option<String>, Error> {
+    let path = dir.join(name);
+
+    if path.is_file() {
+        return std::fs::read_to_string(&path).map(Some).map_err(|err| {
+            Error::new(ErrorKind::InvalidOperation, "cannot read template").with_source(err)
+        });
+    }
+
+    Ok(BUILTIN
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, source)| (*source).to_string()))
+}
+
+/// Render `page` with the templates in `dir`.
+pub fn render(page: &model::Page, options: &Options, dir: &str) -> Result<String> {
+    let mut env = Environment::new();
+    let path = PathBuf::from(dir);
+
+    env.set_keep_trailing_newline(true);
+    env.set_loader(move |name| load(&path, name));
+
+    let context = context! {
+        services => page.services,
+        types => page.types,
+        options => context! {
+            optimize_for_doxygen => options.optimize_for_doxygen,
+            style => options.style,
+            types_section => options.types_section,
+        },
+    };
+
+    env.get_template("page.md")
+        .and_then(|template| template.render(context))
+        .with_context(|| format!("rendering templates in {dir}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::{build_page, fixtures, proto, Options};
+    use askama::Template;
+    use std::path::PathBuf;
+
+    /// Temporary template directory removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn render_builtin_templates() {
+        let request = fixtures::tree();
+        let types = proto::get_types(&request).unwrap();
+        let dir = TempDir(std::env::temp_dir().join(format!(
+            "protoc-gen-mdbook-runtime-builtin-{}",
+            std::process::id()
+        )));
+        std::fs::create_dir_all(&dir.0).unwrap();
+
+        for parameter in [
+            "",
+            "types:all",
+            "types:all,style:table",
+            "style:table,types_section:page",
+            "types:all,style:table,types_section:page",
+            "optimize_for_doxygen:doxygen,style:table",
+        ] {
+            let options = Options::from_parameter(parameter);
+            let page = build_page(&request, "foo.proto", &types, &options).unwrap();
+            let model = page.model().unwrap();
+
+            // Leading blank lines stem from macro definitions of the compiled-in template.
+            assert_eq!(
+                render(&model, &options, dir.0.to_str().unwrap())
+                    .unwrap()
+                    .trim_start(),
+                page.render().unwrap().trim_start(),
+                "{parameter}"
+            );
+        }
+    }
+
+    #[test]
+    fn render_custom_templates() {
+        let request = fixtures::tree();
+        let types = proto::get_types(&request).unwrap();
+        let options = Options::default();
+        let page = build_page(&request, "foo.proto", &types, &options).unwrap();
+        let dir = TempDir(std::env::temp_dir().join(format!(
+            "protoc-gen-mdbook-runtime-custom-{}",
+            std::process::id()
+        )));
+        std::fs::create_dir_all(&dir.0).unwrap();
+
+        std::fs::write(
+            dir.0.join("message.md"),
+            "{% for field in type.fields %}{{ field.name }}: {{ field.type }}\
+             {% if field.recursive %} (recursive){% endif %}{% endfor %}\n",
+        )
+        .unwrap();
+
+        let content = render(&page.model().unwrap(), &options, dir.0.to_str().unwrap()).unwrap();
+        assert!(content.contains("## foo.Tree"));
+        assert!(content.contains("next: Node (recursive)\n"));
+    }
+}
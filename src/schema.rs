@@ -0,0 +1,155 @@
+//! Versioned JSON schema of the documentation written with `format:json`.
+//!
+//! Unlike the [`model`](crate::model) passed to templates it only holds what is declared in the
+//! proto files, without anything depending on how or where the types are rendered.
+
+use crate::model::{self, Enum, Oneof};
+use crate::proto;
+use serde::Serialize;
+
+/// Version of the JSON [`Document`] schema, incremented on incompatible changes.
+pub const VERSION: u32 = 1;
+
+/// JSON document generated for one or more proto files.
+#[derive(Serialize)]
+pub struct Document<'a> {
+    pub version: u32,
+    pub files: Vec<File<'a>>,
+}
+
+/// All services and top-level types of a proto file.
+#[derive(Serialize)]
+pub struct File<'a> {
+    pub name: &'a str,
+    pub package: &'a str,
+    pub services: Vec<Service<'a>>,
+    pub types: Vec<Type<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct Service<'a> {
+    pub name: &'a str,
+    pub package: &'a str,
+    /// Package-qualified name or just the name if there is no package.
+    pub full_name: String,
+    pub description: &'a str,
+    pub deprecated: bool,
+    /// All methods in order of declaration.
+    pub methods: Vec<Method<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct Method<'a> {
+    pub name: &'a str,
+    /// One of `unary`, `client streaming`, `server streaming` or `bidi streaming`.
+    pub call_type: String,
+    pub description: &'a str,
+    pub deprecated: bool,
+    /// Package-qualified name of the input type.
+    pub input_type: &'a str,
+    /// Package-qualified name of the output type.
+    pub output_type: &'a str,
+}
+
+/// Message or enum type, distinguished by `kind` being `message` or `enum`.
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Type<'a> {
+    Message(Message<'a>),
+    Enum(Enum<'a>),
+}
+
+#[derive(Serialize)]
+pub struct Message<'a> {
+    pub name: &'a str,
+    pub full_name: &'a str,
+    pub description: &'a str,
+    /// Fields ordered by number, including those of `oneofs`.
+    pub fields: Vec<Field<'a>>,
+    pub oneofs: Vec<Oneof<'a>>,
+    pub nested: Vec<Message<'a>>,
+    pub enums: Vec<Enum<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct Field<'a> {
+    pub name: &'a str,
+    /// Type as declared, e.g. `string`, `Foo` or `map<string, Foo>`.
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// Package-qualified names of the message and enum types in `ty`.
+    pub type_names: Vec<&'a str>,
+    pub number: i32,
+    pub optional: bool,
+    pub repeated: bool,
+    /// Name of the enclosing `oneof`.
+    pub oneof: Option<&'a str>,
+    pub leading_comments: &'a str,
+    pub trailing_comments: &'a str,
+}
+
+impl<'a> From<proto::Service<'a>> for Service<'a> {
+    fn from(service: proto::Service<'a>) -> Self {
+        let methods = service
+            .methods
+            .iter()
+            .map(|method| Method {
+                name: method.name,
+                call_type: method.call_type.to_string(),
+                description: method.description,
+                deprecated: method.deprecated,
+                input_type: &method.input_type.full_name()[1..],
+                output_type: &method.output_type.full_name()[1..],
+            })
+            .collect();
+
+        Self {
+            name: service.name,
+            package: service.package,
+            full_name: proto::qualified_name(service.package, service.name),
+            description: service.description,
+            deprecated: service.deprecated,
+            methods,
+        }
+    }
+}
+
+impl<'a> From<&'a proto::Types<'a>> for Type<'a> {
+    fn from(ty: &'a proto::Types<'a>) -> Self {
+        match ty {
+            proto::Types::Message(m) => Self::Message(model::Message::from(m, &[]).into()),
+            proto::Types::Enum(e) => Self::Enum(Enum::from(e)),
+        }
+    }
+}
+
+impl<'a> From<model::Message<'a>> for Message<'a> {
+    /// Convert message of the template model, leaving out whether fields close a cycle.
+    fn from(message: model::Message<'a>) -> Self {
+        let fields = message
+            .fields
+            .into_iter()
+            .map(|field| Field {
+                name: field.name,
+                ty: field.ty,
+                type_names: field.type_names,
+                number: field.number,
+                optional: field.optional,
+                repeated: field.repeated,
+                oneof: field.oneof,
+                leading_comments: field.leading_comments,
+                trailing_comments: field.trailing_comments,
+            })
+            .collect();
+
+        Self {
+            name: message.name,
+            full_name: message.full_name,
+            description: message.description,
+            fields,
+            oneofs: message.oneofs,
+            nested: message.nested.into_iter().map(Self::from).collect(),
+            enums: message.enums,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+// Protocol buffers language definition for the highlight.js version bundled with mdBook, which
+// does not include it. Code blocks are highlighted again once the language is registered.
+hljs.registerLanguage("protobuf", function (hljs) {
+  return {
+    name: "Protocol Buffers",
+    keywords: {
+      keyword:
+        "package import option optional required repeated group oneof map reserved " +
+        "extensions to max syntax",
+      type:
+        "double float int32 int64 uint32 uint64 sint32 sint64 fixed32 fixed64 sfixed32 " +
+        "sfixed64 bool string bytes",
+      literal: "true false",
+    },
+    contains: [
+      hljs.QUOTE_STRING_MODE,
+      hljs.NUMBER_MODE,
+      hljs.C_LINE_COMMENT_MODE,
+      hljs.C_BLOCK_COMMENT_MODE,
+      {
+        className: "class",
+        beginKeywords: "message enum service",
+        end: /\{/,
+        illegal: /\n/,
+        contains: [hljs.inherit(hljs.TITLE_MODE, { starts: { endsWithParent: true, excludeEnd: true } })],
+      },
+      {
+        className: "function",
+        beginKeywords: "rpc",
+        end: /[{;]/,
+        excludeEnd: true,
+        keywords: "rpc returns stream",
+      },
+    ],
+  };
+});
+
+document.querySelectorAll("code.language-protobuf").forEach(function (block) {
+  (hljs.highlightElement || hljs.highlightBlock)(block);
+});